@@ -1,5 +1,7 @@
 use analyzer_protos::shared::whitelist_service_server::{WhitelistService, WhitelistServiceServer};
-use analyzer_protos::shared::{WhitelistItem, WhitelistRequest, WhitelistResponse};
+use analyzer_protos::shared::{
+    DevDumpRequest, DevDumpResponse, WhitelistItem, WhitelistRequest, WhitelistResponse,
+};
 use sqlx::{PgPool, Pool, Postgres, Row};
 use std::collections::HashSet;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
@@ -73,6 +75,11 @@ FROM token_stats ts
     ORDER BY total_profit_sol DESC
 "#;
 
+const INSERT_DEV_DUMP_SQL: &str = r#"
+INSERT INTO dev_dumps (token_creator, token_address, seller, signature, token_amount, detected_at)
+VALUES ($1, $2, $3, $4, $5, $6)
+"#;
+
 pub struct MyService {
     pool: Arc<Pool<Postgres>>,
 }
@@ -144,6 +151,28 @@ impl WhitelistService for MyService {
             filtered_items,
         }))
     }
+
+    async fn report_dev_dump(
+        &self,
+        request: Request<DevDumpRequest>,
+    ) -> Result<Response<DevDumpResponse>, Status> {
+        let dump = request.into_inner();
+
+        sqlx::query(INSERT_DEV_DUMP_SQL)
+            .bind(&dump.token_creator)
+            .bind(&dump.token_address)
+            .bind(&dump.seller)
+            .bind(&dump.signature)
+            .bind(dump.token_amount)
+            .bind(dump.detected_at)
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(DevDumpResponse {
+            result: "ok".to_string(),
+        }))
+    }
 }
 
 pub async fn start_server_thread(pool: Arc<Pool<Postgres>>) {
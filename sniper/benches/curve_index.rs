@@ -0,0 +1,61 @@
+// 价格监听每条交易消息查找持仓的开销: 旧的字符串线性扫描 vs CURVE_INDEX
+//
+// cargo bench -p sniper --bench curve_index
+use sniper::services::transaction_processor::{CURVE_INDEX, mint_for_keys};
+use solana_sdk::pubkey::Pubkey;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const MESSAGES: usize = 200_000;
+// 一笔 pump 交易大约 16-24 个账户
+const KEYS_PER_TX: usize = 20;
+
+fn bench(name: &str, positions: usize, mut f: impl FnMut() -> Option<Pubkey>) {
+    // 预热
+    for _ in 0..1_000 {
+        black_box(f());
+    }
+
+    let start = Instant::now();
+    for _ in 0..MESSAGES {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "{:<10} 持仓 {:>4}: {:>10.1} ns/消息",
+        name,
+        positions,
+        per_message(elapsed)
+    );
+}
+
+fn per_message(elapsed: Duration) -> f64 {
+    elapsed.as_nanos() as f64 / MESSAGES as f64
+}
+
+fn main() {
+    for positions in [1, 10, 50, 200] {
+        CURVE_INDEX.clear();
+        let table = (0..positions)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique().to_string()))
+            .collect::<Vec<_>>();
+        for (mint, curve) in &table {
+            CURVE_INDEX.insert(curve.parse().unwrap(), *mint);
+        }
+
+        // 未命中是绝大多数情况
+        let keys = (0..KEYS_PER_TX)
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+
+        bench("legacy", positions, || {
+            let accounts_in_tx: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+            table.iter().find_map(|(mint, curve)| {
+                accounts_in_tx.contains(curve).then_some(*mint)
+            })
+        });
+
+        bench("indexed", positions, || mint_for_keys(&keys));
+    }
+}
@@ -0,0 +1,161 @@
+use aes_gcm_siv::aead::{Aead, KeyInit};
+use aes_gcm_siv::{Aes256GcmSiv, Nonce};
+use anyhow::{Result, anyhow};
+use base64::{Engine, engine::general_purpose};
+use hmac::Hmac;
+use once_cell::sync::OnceCell;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer, read_keypair_file};
+use std::env;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+// 新建 keystore 的 PBKDF2 迭代次数
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+const KDF: &str = "pbkdf2-hmac-sha256";
+const CIPHER: &str = "aes-256-gcm-siv";
+
+// 口令加密的私钥文件，私钥只在启动时解密一次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u32,
+    // 明文公钥，用于核对口令和展示
+    pub pubkey: String,
+    pub kdf: String,
+    pub iterations: u32,
+    pub cipher: String,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase.as_bytes(), salt, iterations, key.as_mut());
+    key
+}
+
+pub fn encrypt_keypair(keypair: &Keypair, passphrase: &str, iterations: u32) -> Result<Keystore> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    rand::rng().fill_bytes(&mut salt);
+    rand::rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt, iterations);
+    let cipher = Aes256GcmSiv::new_from_slice(key.as_ref()).map_err(|e| anyhow!(e.to_string()))?;
+    let secret = Zeroizing::new(keypair.to_bytes());
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), secret.as_ref())
+        .map_err(|_| anyhow!("加密失败"))?;
+
+    Ok(Keystore {
+        version: 1,
+        pubkey: keypair.pubkey().to_string(),
+        kdf: KDF.to_string(),
+        iterations,
+        cipher: CIPHER.to_string(),
+        salt: general_purpose::STANDARD.encode(salt),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    })
+}
+
+pub fn decrypt_keystore(keystore: &Keystore, passphrase: &str) -> Result<Keypair> {
+    if keystore.version != 1 || keystore.kdf != KDF || keystore.cipher != CIPHER {
+        return Err(anyhow!(
+            "不支持的 keystore 格式: v{} {} {}",
+            keystore.version,
+            keystore.kdf,
+            keystore.cipher
+        ));
+    }
+
+    let salt = general_purpose::STANDARD.decode(&keystore.salt)?;
+    let nonce = general_purpose::STANDARD.decode(&keystore.nonce)?;
+    let ciphertext = general_purpose::STANDARD.decode(&keystore.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(anyhow!("keystore nonce 长度错误"));
+    }
+
+    let key = derive_key(passphrase, &salt, keystore.iterations);
+    let cipher = Aes256GcmSiv::new_from_slice(key.as_ref()).map_err(|e| anyhow!(e.to_string()))?;
+    let secret = Zeroizing::new(
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| anyhow!("口令错误或 keystore 已损坏"))?,
+    );
+    let keypair = Keypair::from_bytes(&secret).map_err(|e| anyhow!(e.to_string()))?;
+
+    if keypair.pubkey() != Pubkey::from_str(&keystore.pubkey)? {
+        return Err(anyhow!("keystore 公钥与私钥不一致"));
+    }
+    Ok(keypair)
+}
+
+// 同一次启动中所有 keystore 共用一个口令，只询问一次
+static PASSPHRASE: OnceCell<Zeroizing<String>> = OnceCell::new();
+
+// 口令优先从 KEYSTORE_PASSPHRASE_FILE 指向的文件读取，否则在终端输入
+fn passphrase() -> Result<&'static str> {
+    let passphrase = PASSPHRASE.get_or_try_init(|| -> Result<Zeroizing<String>> {
+        if let Some(path) = env::var("KEYSTORE_PASSPHRASE_FILE")
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+        {
+            let content = Zeroizing::new(std::fs::read_to_string(&path)?);
+            return Ok(Zeroizing::new(content.trim_end_matches(['\r', '\n']).to_string()));
+        }
+        Ok(Zeroizing::new(rpassword::prompt_password("请输入 keystore 口令: ")?))
+    })?;
+    Ok(passphrase.as_str())
+}
+
+pub fn load_keystore(path: &str) -> Result<Keypair> {
+    let content = std::fs::read_to_string(path)?;
+    let keystore: Keystore = serde_json::from_str(&content)?;
+    decrypt_keystore(&keystore, passphrase()?).map_err(|e| anyhow!("{}: {}", path, e))
+}
+
+// Solana CLI 格式的 JSON 私钥文件
+pub fn load_keypair_file(path: &str) -> Result<Keypair> {
+    read_keypair_file(path).map_err(|e| anyhow!("读取私钥文件 {} 失败: {}", path, e))
+}
+
+pub fn keypair_from_base58(private_key: &str) -> Result<Keypair> {
+    let bytes = Zeroizing::new(bs58::decode(private_key).into_vec()?);
+    Keypair::from_bytes(&bytes).map_err(|e| anyhow!(e.to_string()))
+}
+
+// 把 Solana CLI 私钥文件加密为 keystore，口令在终端输入两次
+pub fn encrypt_keypair_file(keypair_path: &str, keystore_path: &str) -> Result<()> {
+    let keypair = load_keypair_file(keypair_path)?;
+    let passphrase = Zeroizing::new(rpassword::prompt_password("设置 keystore 口令: ")?);
+    let confirm = Zeroizing::new(rpassword::prompt_password("再次输入口令: ")?);
+    if passphrase.is_empty() || *passphrase != *confirm {
+        return Err(anyhow!("两次输入的口令不一致或为空"));
+    }
+
+    let keystore = encrypt_keypair(&keypair, &passphrase, DEFAULT_ITERATIONS)?;
+    std::fs::write(keystore_path, serde_json::to_string_pretty(&keystore)?)?;
+    println!("已生成 keystore: {}, 公钥: {}", keystore_path, keystore.pubkey);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystore_roundtrip() {
+        let keypair = Keypair::new();
+        let keystore = encrypt_keypair(&keypair, "correct horse", 1_000).unwrap();
+
+        let decrypted = decrypt_keystore(&keystore, "correct horse").unwrap();
+        assert_eq!(decrypted.pubkey(), keypair.pubkey());
+        assert!(decrypt_keystore(&keystore, "wrong").is_err());
+    }
+}
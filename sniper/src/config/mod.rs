@@ -3,16 +3,27 @@ use solana_sdk::pubkey::Pubkey;
 use std::io::{Error, ErrorKind};
 use std::{env, fs};
 
+pub mod keystore;
+
 // 定义要查找的程序ID (Base58格式)
 pub const PUMP_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
 pub static mut BUY_ENABLED: Lazy<bool> = Lazy::new(|| env::var("BUY_ENABLED").expect("没有设置 BUY_ENABLED").parse().unwrap());
 pub static NONCE_PUBKEY: Lazy<String> = Lazy::new(|| env::var("NONCE_PUBKEY").expect("没有设置 NONCE_PUBKEY"));
-pub static PRIVATE_KEY: Lazy<String> = Lazy::new(|| env::var("PRIVATE_KEY").expect("没有设置 PRIVATE_KEY"));
-pub static PUBLIC_KEY: Lazy<String> = Lazy::new(|| env::var("PUBLIC_KEY").expect("没有设置 PUBLIC_KEY"));
+// 私钥来源按 KEYSTORE_PATH、KEYPAIR_PATH、PRIVATE_KEY 的顺序选择，配置了 wallets.json 时不使用
+// 空字符串与未设置相同，模板中留空的项不会挡住后面的来源
+pub static KEYSTORE_PATH: Lazy<Option<String>> = Lazy::new(|| env::var("KEYSTORE_PATH").ok().filter(|v| !v.trim().is_empty()));
+pub static KEYPAIR_PATH: Lazy<Option<String>> = Lazy::new(|| env::var("KEYPAIR_PATH").ok().filter(|v| !v.trim().is_empty()));
+pub static PRIVATE_KEY: Lazy<Option<String>> = Lazy::new(|| env::var("PRIVATE_KEY").ok().filter(|v| !v.trim().is_empty()));
+// 可选，钱包地址由私钥推导，设置时启动自检会核对是否一致
+pub static PUBLIC_KEY: Lazy<Option<String>> = Lazy::new(|| env::var("PUBLIC_KEY").ok().filter(|v| !v.trim().is_empty()));
 pub static JITO_RPC_ENDPOINTS: Lazy<String> = Lazy::new(|| env::var("JITO_RPC_ENDPOINTS").expect("没有设置 JITO_RPC_ENDPOINTS"));
 pub static ZERO_SLOT_RPC_ENDPOINTS: Lazy<String> = Lazy::new(|| env::var("ZERO_SLOT_RPC_ENDPOINTS").expect("没有设置 ZERO_SLOT_RPC_ENDPOINTS"));
 pub static JITO_SHRED_URL: Lazy<String> = Lazy::new(|| env::var("JITO_SHRED_URL").expect("没有设置 JITO_SHRED_URL"));
+// analyzer 服务地址 (白名单、开发者砸盘上报)，未设置时使用本机
+pub static ANALYZER_URL: Lazy<String> = Lazy::new(|| env::var("ANALYZER_URL").ok().filter(|v| !v.trim().is_empty()).unwrap_or_else(|| "http://127.0.0.1:8090".to_string()));
 pub static mut MAX_SOL: Lazy<f64> = Lazy::new(|| env::var("MAX_SOL").expect("没有设置 MAX_SOL").parse().unwrap());
+// 买入数量的滑点容忍度 (基点)，按同批次其他买入之后的曲线报价再缩减
+pub static BUY_SLIPPAGE_BPS: Lazy<u64> = Lazy::new(|| env::var("BUY_SLIPPAGE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(600));
 pub static mut JITO_FEE: Lazy<f64> = Lazy::new(|| env::var("JITO_FEE").expect("没有设置 JITO_FEE").parse().unwrap());
 pub static mut ZERO_SLOT_BUY_FEE: Lazy<f64> = Lazy::new(|| env::var("ZERO_SLOT_BUY_FEE").expect("没有设置 ZERO_SLOT_BUY_FEE").parse().unwrap());
 pub static mut ZERO_SLOT_SELL_FEE: Lazy<f64> = Lazy::new(|| env::var("ZERO_SLOT_SELL_FEE").expect("没有设置 ZERO_SLOT_SELL_FEE").parse().unwrap());
@@ -27,3 +38,35 @@ pub static mut WHITELIST_HOLD_LESS_5_SEC_COUNT: Lazy<i64> = Lazy::new(|| env::va
 pub static mut WHITELIST_MIN_HOLD: Lazy<i64> = Lazy::new(|| env::var("WHITELIST_MIN_HOLD").expect("没有设置 WHITELIST_MIN_HOLD").parse().unwrap());
 pub static mut WHITELIST_AVG_USER: Lazy<i64> = Lazy::new(|| env::var("WHITELIST_AVG_USER").expect("没有设置 WHITELIST_AVG_USER").parse().unwrap());
 pub static mut WHITELIST_TOP_3_BUY: Lazy<f64> = Lazy::new(|| env::var("WHITELIST_TOP_3_BUY").expect("没有设置 WHITELIST_TOP_3_BUY").parse().unwrap());
+
+// 元数据筛查配置，未设置时关闭
+pub static mut METADATA_SCREEN_ENABLED: Lazy<bool> = Lazy::new(|| env::var("METADATA_SCREEN_ENABLED").ok().and_then(|v| v.parse().ok()).unwrap_or(false));
+pub static METADATA_TIMEOUT_MS: Lazy<u64> = Lazy::new(|| env::var("METADATA_TIMEOUT_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(1500));
+
+// 跟单开关，跟单钱包见 copy_wallets.json
+pub static mut COPY_TRADE_ENABLED: Lazy<bool> = Lazy::new(|| env::var("COPY_TRADE_ENABLED").ok().and_then(|v| v.parse().ok()).unwrap_or(false));
+
+// 紧急退出（开发者砸盘）的卖出小费
+pub static EMERGENCY_SELL_FEE: Lazy<f64> = Lazy::new(|| env::var("EMERGENCY_SELL_FEE").ok().and_then(|v| v.parse().ok()).unwrap_or(0.002));
+pub static EMERGENCY_CU_PRICE: Lazy<u64> = Lazy::new(|| env::var("EMERGENCY_CU_PRICE").ok().and_then(|v| v.parse().ok()).unwrap_or(200_000));
+// 卖出最少到手 SOL 的滑点容忍度 (基点)，按最新储备报价后缩减
+pub static SELL_SLIPPAGE_BPS: Lazy<u64> = Lazy::new(|| env::var("SELL_SLIPPAGE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(1000));
+// 紧急退出未成交时放宽到的滑点 (基点)，10000 表示不限最少到手
+pub static EMERGENCY_SELL_SLIPPAGE_BPS: Lazy<u64> = Lazy::new(|| env::var("EMERGENCY_SELL_SLIPPAGE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(5000));
+
+// 持仓对账
+pub static RECONCILE_INTERVAL_SECS: Lazy<u64> = Lazy::new(|| env::var("RECONCILE_INTERVAL_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(30));
+pub static RECONCILE_SUBSCRIBE: Lazy<bool> = Lazy::new(|| env::var("RECONCILE_SUBSCRIBE").ok().and_then(|v| v.parse().ok()).unwrap_or(false));
+pub static RECONCILE_ADOPT: Lazy<bool> = Lazy::new(|| env::var("RECONCILE_ADOPT").ok().and_then(|v| v.parse().ok()).unwrap_or(true));
+
+// 风控限制，超出时拒绝买入，当日亏损超限时自动停止买入
+pub static RISK_MAX_OPEN_POSITIONS: Lazy<usize> = Lazy::new(|| env::var("RISK_MAX_OPEN_POSITIONS").ok().and_then(|v| v.parse().ok()).unwrap_or(5));
+pub static RISK_MAX_SOL_DEPLOYED: Lazy<f64> = Lazy::new(|| env::var("RISK_MAX_SOL_DEPLOYED").ok().and_then(|v| v.parse().ok()).unwrap_or(0.5));
+pub static RISK_MAX_BUYS_PER_MINUTE: Lazy<usize> = Lazy::new(|| env::var("RISK_MAX_BUYS_PER_MINUTE").ok().and_then(|v| v.parse().ok()).unwrap_or(10));
+pub static RISK_DAILY_LOSS_LIMIT: Lazy<f64> = Lazy::new(|| env::var("RISK_DAILY_LOSS_LIMIT").ok().and_then(|v| v.parse().ok()).unwrap_or(0.2));
+pub static RISK_CREATOR_COOLDOWN_SECS: Lazy<u64> = Lazy::new(|| env::var("RISK_CREATOR_COOLDOWN_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(600));
+
+// 钱包 SOL 余额: 买入后至少保留 SOL_RESERVE，低于 SOL_PAUSE_THRESHOLD 暂停买入
+pub static SOL_RESERVE: Lazy<f64> = Lazy::new(|| env::var("SOL_RESERVE").ok().and_then(|v| v.parse().ok()).unwrap_or(0.05));
+pub static SOL_PAUSE_THRESHOLD: Lazy<f64> = Lazy::new(|| env::var("SOL_PAUSE_THRESHOLD").ok().and_then(|v| v.parse().ok()).unwrap_or(0.1));
+pub static SOL_BALANCE_POLL_MS: Lazy<u64> = Lazy::new(|| env::var("SOL_BALANCE_POLL_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(2000));
@@ -1,10 +1,17 @@
 use sniper::JitoClient;
+use sniper::config::keystore::encrypt_keypair_file;
 // 引入所需的库
 use std::io::Error;
 
 fn main() -> Result<(), Error> {
     dotenvy::dotenv().ok();
 
+    // sniper encrypt-keypair <keypair.json> <keystore.json>
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.len() == 4 && args[1] == "encrypt-keypair" {
+        return encrypt_keypair_file(&args[2], &args[3]).map_err(Error::other);
+    }
+
     // 启动客户端逻辑
     JitoClient::start()
 }
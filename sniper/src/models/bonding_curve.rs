@@ -0,0 +1,346 @@
+use crate::monitor::TradeEvent;
+use crate::services::pump_global::pump_global;
+use borsh::BorshDeserialize;
+use lazy_static::lazy_static;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::RwLock;
+
+// PUMP 联合曲线计算，全部使用 u128 整数运算，取整方式与链上程序保持一致
+
+pub const FEE_DENOMINATOR: u128 = 10_000;
+pub const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0; // 10^9
+pub const TOKEN_DECIMALS: f64 = 1_000_000.0; // 10^6
+
+// Global 账户未加载时使用的默认初始储备
+pub const INITIAL_VIRTUAL_SOL_RESERVES: u64 = 30_000_000_000;
+pub const INITIAL_VIRTUAL_TOKEN_RESERVES: u64 = 1_073_000_000_000_000;
+pub const INITIAL_REAL_TOKEN_RESERVES: u64 = 793_100_000_000_000;
+
+// 手续费配置，单位: 基点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeConfig {
+    pub fee_basis_points: u64,
+    pub creator_fee_basis_points: u64,
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            fee_basis_points: 95,
+            creator_fee_basis_points: 5,
+        }
+    }
+}
+
+lazy_static! {
+    // 最近一次 TradeEvent 中的费率
+    pub static ref FEE_CONFIG: RwLock<FeeConfig> = RwLock::new(FeeConfig::default());
+}
+
+pub fn current_fee_config() -> FeeConfig {
+    *FEE_CONFIG.read().unwrap()
+}
+
+pub fn update_fee_config(event: &TradeEvent) {
+    let fees = FeeConfig::from_trade_event(event);
+    if fees != current_fee_config() {
+        *FEE_CONFIG.write().unwrap() = fees;
+        println!("[费率] 已更新: {:?}", fees);
+    }
+}
+
+impl FeeConfig {
+    pub fn from_trade_event(event: &TradeEvent) -> Self {
+        Self {
+            fee_basis_points: event.fee_basis_points,
+            creator_fee_basis_points: event.creator_fee_basis_points,
+        }
+    }
+
+    pub fn total_basis_points(&self) -> u64 {
+        self.fee_basis_points + self.creator_fee_basis_points
+    }
+
+    // 协议费和创作者费分别向上取整
+    pub fn fee(&self, amount: u64) -> u64 {
+        ceil_div(amount as u128 * self.fee_basis_points as u128, FEE_DENOMINATOR) as u64
+            + ceil_div(
+                amount as u128 * self.creator_fee_basis_points as u128,
+                FEE_DENOMINATOR,
+            ) as u64
+    }
+}
+
+// 联合曲线储备
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CurveState {
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub real_token_reserves: u64,
+}
+
+impl CurveState {
+    // 新发行代币的曲线，参数来自 Global 账户
+    pub fn initial() -> Self {
+        let global = pump_global();
+        Self {
+            virtual_sol_reserves: global.initial_virtual_sol_reserves,
+            virtual_token_reserves: global.initial_virtual_token_reserves,
+            real_sol_reserves: 0,
+            real_token_reserves: global.initial_real_token_reserves,
+        }
+    }
+
+    pub fn from_trade_event(event: &TradeEvent) -> Self {
+        Self {
+            virtual_sol_reserves: event.virtual_sol_reserves,
+            virtual_token_reserves: event.virtual_token_reserves,
+            real_sol_reserves: event.real_sol_reserves,
+            real_token_reserves: event.real_token_reserves,
+        }
+    }
+
+    // 当前价格 (SOL/Token)，按 PUMP 代币精度换算，仅用于展示和比较涨跌幅
+    pub fn price(&self) -> f64 {
+        self.price_with_decimals(TOKEN_DECIMALS)
+    }
+
+    // 按代币精度 (10^decimals) 计算价格，不同发射台的代币精度不同
+    pub fn price_with_decimals(&self, token_decimals: f64) -> f64 {
+        if self.virtual_token_reserves == 0 {
+            return 0.0;
+        }
+
+        (self.virtual_sol_reserves as f64 / LAMPORTS_PER_SOL)
+            / (self.virtual_token_reserves as f64 / token_decimals)
+    }
+
+    // 买入报价: 投入 sol_amount（含手续费）可得到的代币数量
+    pub fn buy_quote(&self, sol_amount: u64, fees: &FeeConfig) -> u64 {
+        if sol_amount == 0 || self.virtual_token_reserves == 0 {
+            return 0;
+        }
+
+        let input_amount = sol_amount as u128 * FEE_DENOMINATOR
+            / (fees.total_basis_points() as u128 + FEE_DENOMINATOR);
+        let tokens = input_amount * self.virtual_token_reserves as u128
+            / (self.virtual_sol_reserves as u128 + input_amount);
+
+        (tokens as u64).min(self.real_token_reserves)
+    }
+
+    // 按滑点容忍度缩减的买入数量，成交前曲线再上涨 slippage_bps 以内仍能成交
+    pub fn buy_quote_with_slippage(&self, sol_amount: u64, fees: &FeeConfig, slippage_bps: u64) -> u64 {
+        let quoted = self.buy_quote(sol_amount, fees) as u128;
+        (quoted * FEE_DENOMINATOR.saturating_sub(slippage_bps as u128) / FEE_DENOMINATOR) as u64
+    }
+
+    // 买入成本: 买入 token_amount 需要支付的 SOL（含手续费），即 max_sol_cost 的下限
+    pub fn buy_cost(&self, token_amount: u64, fees: &FeeConfig) -> u64 {
+        let sol_cost = self.buy_cost_before_fees(token_amount);
+        sol_cost.saturating_add(fees.fee(sol_cost))
+    }
+
+    fn buy_cost_before_fees(&self, token_amount: u64) -> u64 {
+        let amount = token_amount.min(self.real_token_reserves) as u128;
+        if amount == 0 {
+            return 0;
+        }
+        if amount >= self.virtual_token_reserves as u128 {
+            return u64::MAX;
+        }
+
+        let sol_cost = amount * self.virtual_sol_reserves as u128
+            / (self.virtual_token_reserves as u128 - amount)
+            + 1;
+
+        u64::try_from(sol_cost).unwrap_or(u64::MAX)
+    }
+
+    // 卖出报价: 卖出 token_amount 实际到手的 SOL（已扣手续费）
+    pub fn sell_quote(&self, token_amount: u64, fees: &FeeConfig) -> u64 {
+        let sol_out = self.sell_output_before_fees(token_amount);
+        sol_out.saturating_sub(fees.fee(sol_out))
+    }
+
+    // 卖出的最少到手 SOL，成交前曲线再下跌 slippage_bps 以内仍能成交
+    pub fn sell_quote_with_slippage(&self, token_amount: u64, fees: &FeeConfig, slippage_bps: u64) -> u64 {
+        let quoted = self.sell_quote(token_amount, fees) as u128;
+        (quoted * FEE_DENOMINATOR.saturating_sub(slippage_bps as u128) / FEE_DENOMINATOR) as u64
+    }
+
+    fn sell_output_before_fees(&self, token_amount: u64) -> u64 {
+        if token_amount == 0 {
+            return 0;
+        }
+
+        let sol_out = token_amount as u128 * self.virtual_sol_reserves as u128
+            / (self.virtual_token_reserves as u128 + token_amount as u128);
+
+        sol_out as u64
+    }
+
+    // 买入 sol_amount（含手续费）造成的价格冲击，单位: 基点
+    pub fn buy_price_impact_bps(&self, sol_amount: u64, fees: &FeeConfig) -> u64 {
+        let tokens = self.buy_quote(sol_amount, fees);
+        let after = self.after_buy(tokens);
+        price_change_bps(self, &after)
+    }
+
+    // 卖出 token_amount 造成的价格冲击，单位: 基点
+    pub fn sell_price_impact_bps(&self, token_amount: u64) -> u64 {
+        let after = self.after_sell(token_amount);
+        price_change_bps(self, &after)
+    }
+
+    // 买入 token_amount 后的曲线
+    pub fn after_buy(&self, token_amount: u64) -> Self {
+        let amount = token_amount.min(self.real_token_reserves);
+        let sol_cost = self.buy_cost_before_fees(amount);
+
+        Self {
+            virtual_sol_reserves: self.virtual_sol_reserves.saturating_add(sol_cost),
+            virtual_token_reserves: self.virtual_token_reserves.saturating_sub(amount),
+            real_sol_reserves: self.real_sol_reserves.saturating_add(sol_cost),
+            real_token_reserves: self.real_token_reserves - amount,
+        }
+    }
+
+    // 卖出 token_amount 后的曲线
+    pub fn after_sell(&self, token_amount: u64) -> Self {
+        let sol_out = self.sell_output_before_fees(token_amount);
+
+        Self {
+            virtual_sol_reserves: self.virtual_sol_reserves.saturating_sub(sol_out),
+            virtual_token_reserves: self.virtual_token_reserves.saturating_add(token_amount),
+            real_sol_reserves: self.real_sol_reserves.saturating_sub(sol_out),
+            real_token_reserves: self.real_token_reserves.saturating_add(token_amount),
+        }
+    }
+}
+
+// 链上 BondingCurve 账户，8 字节 discriminator 之后的字段
+#[derive(Debug, Clone, PartialEq, BorshDeserialize)]
+pub struct BondingCurveAccount {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+    pub creator: Pubkey,
+}
+
+impl BondingCurveAccount {
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let mut rest = data.get(8..)?;
+        Self::deserialize(&mut rest).ok()
+    }
+
+    pub fn curve(&self) -> CurveState {
+        CurveState {
+            virtual_sol_reserves: self.virtual_sol_reserves,
+            virtual_token_reserves: self.virtual_token_reserves,
+            real_sol_reserves: self.real_sol_reserves,
+            real_token_reserves: self.real_token_reserves,
+        }
+    }
+}
+
+fn ceil_div(a: u128, b: u128) -> u128 {
+    a.div_ceil(b)
+}
+
+// 两个曲线状态之间价格变动的绝对值，单位: 基点
+fn price_change_bps(before: &CurveState, after: &CurveState) -> u64 {
+    // price = sol / token，交叉相乘避免精度损失
+    let before_value = before.virtual_sol_reserves as u128 * after.virtual_token_reserves as u128;
+    let after_value = after.virtual_sol_reserves as u128 * before.virtual_token_reserves as u128;
+    if before_value == 0 {
+        return 0;
+    }
+
+    let diff = after_value.abs_diff(before_value);
+    u64::try_from(diff * FEE_DENOMINATOR / before_value).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buy_and_sell_quotes() {
+        let curve = CurveState::initial();
+        let fees = FeeConfig::default();
+
+        // 1 SOL 扣除 1% 手续费后约买到 34.28M 代币
+        let tokens = curve.buy_quote(1_000_000_000, &fees);
+        assert_eq!(tokens, 34_281_150_129_545);
+
+        // 按报价买入的成本不会超过投入
+        let cost = curve.buy_cost(tokens, &fees);
+        assert!(cost <= 1_000_000_000);
+        assert!(cost >= 999_999_000);
+
+        // 立即卖出，扣两次手续费后到手约 0.98 SOL
+        let after = curve.after_buy(tokens);
+        let sol_out = after.sell_quote(tokens, &fees);
+        assert!(sol_out < 1_000_000_000);
+        assert!(sol_out > 980_000_000);
+    }
+
+    #[test]
+    fn test_buy_quote_with_slippage() {
+        let curve = CurveState::initial();
+        let fees = FeeConfig::default();
+        let quoted = curve.buy_quote(10_000_000, &fees);
+
+        assert_eq!(curve.buy_quote_with_slippage(10_000_000, &fees, 0), quoted);
+        assert_eq!(
+            curve.buy_quote_with_slippage(10_000_000, &fees, 600),
+            (quoted as u128 * 94 / 100) as u64
+        );
+
+        // 前面有其他买入时，同样的 SOL 买到更少
+        let crowded = curve.after_buy(50_000_000_000_000);
+        assert!(crowded.buy_quote_with_slippage(10_000_000, &fees, 600) < quoted * 94 / 100);
+    }
+
+    #[test]
+    fn test_sell_quote_with_slippage() {
+        let curve = CurveState::initial().after_buy(50_000_000_000_000);
+        let fees = FeeConfig::default();
+        let quoted = curve.sell_quote(10_000_000_000, &fees);
+
+        assert_eq!(curve.sell_quote_with_slippage(10_000_000_000, &fees, 0), quoted);
+        assert_eq!(
+            curve.sell_quote_with_slippage(10_000_000_000, &fees, 1000),
+            (quoted as u128 * 90 / 100) as u64
+        );
+        // 10000 基点即不限最少到手
+        assert_eq!(curve.sell_quote_with_slippage(10_000_000_000, &fees, 10_000), 0);
+    }
+
+    #[test]
+    fn test_fee_rounds_up() {
+        let fees = FeeConfig {
+            fee_basis_points: 95,
+            creator_fee_basis_points: 5,
+        };
+        assert_eq!(fees.fee(10_000), 100);
+        assert_eq!(fees.fee(1), 2);
+        assert_eq!(fees.fee(0), 0);
+    }
+
+    #[test]
+    fn test_price_impact() {
+        let curve = CurveState::initial();
+        let fees = FeeConfig::default();
+
+        assert_eq!(curve.buy_price_impact_bps(0, &fees), 0);
+        // 3 SOL 买入，价格上涨约 21%
+        let impact = curve.buy_price_impact_bps(3_000_000_000, &fees);
+        assert!((2_000..2_200).contains(&impact), "impact = {}", impact);
+    }
+}
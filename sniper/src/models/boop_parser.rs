@@ -0,0 +1,504 @@
+use crate::models::PumpTransaction;
+use crate::models::bonding_curve::{CurveState, FeeConfig};
+use crate::models::pump_idl::{PumpParseError, decode_args};
+use crate::models::pump_parser::{PumpParser, snipe_budget_lamports};
+use crate::models::venue::{CurveSell, LaunchBuy, Venue, VenueKind, VenueTrade};
+use crate::transaction::{boop_buy_instructions, boop_sell_instructions};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::instruction::CompiledInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+
+// Boop 发射台程序ID
+pub const BOOP_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("boop8hVGQGqehUK2iVEMEnMrL5RbjywRzHKBmBE7ry4");
+
+// Boop指令discriminator
+pub const BOOP_CREATE_TOKEN_DISCRIMINATOR: [u8; 8] = [84, 52, 204, 228, 24, 140, 234, 75];
+pub const BOOP_DEPLOY_BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [180, 89, 199, 76, 168, 236, 217, 138];
+pub const BOOP_BUY_TOKEN_DISCRIMINATOR: [u8; 8] = [138, 127, 14, 91, 38, 87, 115, 105];
+pub const BOOP_SELL_TOKEN_DISCRIMINATOR: [u8; 8] = [109, 61, 40, 187, 230, 176, 135, 174];
+pub const BOOP_BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [23, 183, 248, 55, 96, 216, 172, 96];
+
+// 新发行代币的初始储备和交易手续费，与 Boop Config 账户一致
+// Boop 代币精度为 9 位，价格换算不能用 PUMP 的 6 位
+pub const BOOP_TOKEN_DECIMALS: f64 = 1_000_000_000.0; // 10^9
+pub const BOOP_INITIAL_VIRTUAL_SOL_RESERVES: u64 = 30_000_000_000;
+pub const BOOP_INITIAL_TOKEN_RESERVES: u64 = 1_000_000_000_000_000_000;
+pub const BOOP_SWAP_FEE_BASIS_POINTS: u64 = 100;
+
+// create_token(salt, name, symbol, uri)
+#[derive(Debug, Clone, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct CreateTokenArgs {
+    pub salt: u64,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+// buy_token(buy_amount, amount_out_min): 投入的 SOL 和最少得到的代币
+#[derive(Debug, Clone, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct BuyTokenArgs {
+    pub buy_amount: u64,
+    pub amount_out_min: u64,
+}
+
+// sell_token(sell_amount, amount_out_min): 卖出的代币和最少到手的 SOL
+#[derive(Debug, Clone, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct SellTokenArgs {
+    pub sell_amount: u64,
+    pub amount_out_min: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoopInstructionType {
+    Unknown,
+    CreateToken,
+    DeployBondingCurve,
+    BuyToken,
+    SellToken,
+}
+
+#[derive(Debug, Clone)]
+pub struct BoopInstruction {
+    pub instruction_type: BoopInstructionType,
+    pub accounts: Vec<Pubkey>,
+    pub data: Vec<u8>,
+}
+
+impl BoopInstruction {
+    fn new(data: &[u8], account_indexes: &[u8], account_keys: &[Pubkey]) -> Self {
+        let instruction_type = match data.get(0..8).and_then(|d| <[u8; 8]>::try_from(d).ok()) {
+            Some(BOOP_CREATE_TOKEN_DISCRIMINATOR) => BoopInstructionType::CreateToken,
+            Some(BOOP_DEPLOY_BONDING_CURVE_DISCRIMINATOR) => {
+                BoopInstructionType::DeployBondingCurve
+            }
+            Some(BOOP_BUY_TOKEN_DISCRIMINATOR) => BoopInstructionType::BuyToken,
+            Some(BOOP_SELL_TOKEN_DISCRIMINATOR) => BoopInstructionType::SellToken,
+            _ => BoopInstructionType::Unknown,
+        };
+
+        Self {
+            instruction_type,
+            accounts: account_indexes
+                .iter()
+                .filter_map(|idx| account_keys.get(*idx as usize).copied())
+                .collect(),
+            data: data.to_vec(),
+        }
+    }
+
+    // 账户下标见 utils 中的 boop 账户标签
+    pub fn pubkey(&self, idx: usize) -> Result<Pubkey, PumpParseError> {
+        self.accounts
+            .get(idx)
+            .copied()
+            .ok_or(PumpParseError::MissingAccount(idx))
+    }
+}
+
+// Boop 的 PDA 账户，买卖指令都按 mint 派生
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoopAccounts {
+    pub config: Pubkey,
+    pub vault_authority: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub bonding_curve_vault: Pubkey,
+    pub bonding_curve_sol_vault: Pubkey,
+    pub trading_fees_vault: Pubkey,
+}
+
+impl BoopAccounts {
+    pub fn derive(mint: &Pubkey) -> Self {
+        let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &BOOP_PROGRAM_ID).0;
+        Self {
+            config: pda(&[b"config"]),
+            vault_authority: pda(&[b"vault_authority"]),
+            bonding_curve: pda(&[b"bonding_curve", mint.as_ref()]),
+            bonding_curve_vault: pda(&[b"bonding_curve_vault", mint.as_ref()]),
+            bonding_curve_sol_vault: pda(&[b"bonding_curve_sol_vault", mint.as_ref()]),
+            trading_fees_vault: pda(&[b"trading_fees_vault", mint.as_ref()]),
+        }
+    }
+}
+
+// 链上 Boop BondingCurve 账户，只解码需要的前缀字段
+#[derive(Debug, Clone, PartialEq, BorshDeserialize)]
+pub struct BoopBondingCurveAccount {
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub graduation_target: u64,
+    pub graduation_fee: u64,
+    pub sol_reserves: u64,
+    pub token_reserves: u64,
+    pub damping_term: u8,
+    pub swap_fee_basis_points: u16,
+    pub token_for_stakers_basis_points: u16,
+    // 0: 交易中，其它为已毕业或迁移中
+    pub status: u8,
+}
+
+impl BoopBondingCurveAccount {
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.get(..8)? != BOOP_BONDING_CURVE_DISCRIMINATOR {
+            return None;
+        }
+        let mut rest = &data[8..];
+        Self::deserialize(&mut rest).ok()
+    }
+
+    pub fn complete(&self) -> bool {
+        self.status != 0
+    }
+
+    // 报价按 虚拟储备 + 实际储备 的恒定乘积计算
+    pub fn curve(&self) -> CurveState {
+        CurveState {
+            virtual_sol_reserves: self.virtual_sol_reserves.saturating_add(self.sol_reserves),
+            virtual_token_reserves: self
+                .virtual_token_reserves
+                .saturating_add(self.token_reserves),
+            real_sol_reserves: self.sol_reserves,
+            real_token_reserves: self.token_reserves,
+        }
+    }
+}
+
+pub fn boop_initial_curve() -> CurveState {
+    CurveState {
+        virtual_sol_reserves: BOOP_INITIAL_VIRTUAL_SOL_RESERVES,
+        virtual_token_reserves: BOOP_INITIAL_TOKEN_RESERVES,
+        real_sol_reserves: 0,
+        real_token_reserves: BOOP_INITIAL_TOKEN_RESERVES,
+    }
+}
+
+pub fn boop_fee_config() -> FeeConfig {
+    FeeConfig {
+        fee_basis_points: BOOP_SWAP_FEE_BASIS_POINTS,
+        creator_fee_basis_points: 0,
+    }
+}
+
+// 任意交易中的一笔 Boop 卖出，用于识别开发者砸盘
+#[derive(Debug, Clone)]
+pub struct BoopSell {
+    pub signature: String,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+pub struct BoopParser;
+
+impl BoopParser {
+    pub fn is_target_program(program_id: &Pubkey) -> bool {
+        *program_id == BOOP_PROGRAM_ID
+    }
+
+    pub fn extract_instructions(transaction: &VersionedTransaction) -> Vec<BoopInstruction> {
+        let Some((instructions, account_keys)) =
+            PumpParser::message_parts(&transaction.message, Self::is_target_program)
+        else {
+            return Vec::new();
+        };
+
+        instructions
+            .iter()
+            .filter(|ix: &&CompiledInstruction| {
+                account_keys
+                    .get(ix.program_id_index as usize)
+                    .is_some_and(Self::is_target_program)
+            })
+            .map(|ix| BoopInstruction::new(&ix.data, &ix.accounts, &account_keys))
+            .collect()
+    }
+
+    // 解析 Boop 发币交易: 同一笔交易里 create_token + deploy_bonding_curve
+    // 返回 Ok(None) 表示不是发币交易
+    pub fn parse_transaction(
+        transaction: &VersionedTransaction,
+    ) -> Result<Option<PumpTransaction>, PumpParseError> {
+        Self::parse_launch(transaction, snipe_budget_lamports())
+    }
+
+    // 按指定的狙击金额报价买入数量
+    pub(crate) fn parse_launch(
+        transaction: &VersionedTransaction,
+        max_sol_lamports: u64,
+    ) -> Result<Option<PumpTransaction>, PumpParseError> {
+        let instructions = Self::extract_instructions(transaction);
+        let find = |instruction_type| {
+            instructions
+                .iter()
+                .find(|ix| ix.instruction_type == instruction_type)
+        };
+        let (Some(create_ix), Some(deploy_ix)) = (
+            find(BoopInstructionType::CreateToken),
+            find(BoopInstructionType::DeployBondingCurve),
+        ) else {
+            return Ok(None);
+        };
+
+        let create_args: CreateTokenArgs = decode_args(&create_ix.data)?;
+        // create_token: [0]Mint [2]Payer; deploy_bonding_curve: [2]Bonding_Curve [4]Bonding_Curve_Vault
+        let mint = create_ix.pubkey(0)?;
+        let creator = create_ix.pubkey(2)?;
+        let bonding_curve = deploy_ix.pubkey(2)?;
+        let bonding_curve_vault = deploy_ix.pubkey(4)?;
+
+        // 发币交易里开发者和捆绑钱包的买入，按投入的 SOL 推算曲线
+        let fees = boop_fee_config();
+        let buys = instructions
+            .iter()
+            .filter(|ix| ix.instruction_type == BoopInstructionType::BuyToken)
+            .filter(|ix| ix.pubkey(0).is_ok_and(|account| account == mint))
+            .filter_map(|ix| decode_args::<BuyTokenArgs>(&ix.data).ok())
+            .collect::<Vec<_>>();
+        let curve = buys.iter().fold(boop_initial_curve(), |curve, args| {
+            curve.after_buy(curve.buy_quote(args.buy_amount, &fees))
+        });
+
+        let mut launch = PumpTransaction {
+            venue: VenueKind::Boop,
+            signature: transaction
+                .signatures
+                .first()
+                .map(|signature| signature.to_string())
+                .unwrap_or_default(),
+            mint: mint.to_string(),
+            bonding_curve: bonding_curve.to_string(),
+            associated_bonding_curve: bonding_curve_vault.to_string(),
+            creator: creator.to_string(),
+            name: create_args.name,
+            symbol: create_args.symbol,
+            uri: create_args.uri,
+            buy_amount: buys.first().map(|args| args.amount_out_min).unwrap_or(0),
+            max_sol_cost: buys.first().map(|args| args.buy_amount).unwrap_or(0),
+            ..Default::default()
+        };
+        launch.set_curve_with_budget(curve, max_sol_lamports);
+
+        Ok(Some(launch))
+    }
+
+    // 解析交易中的全部 sell_token 指令
+    pub fn parse_sells(
+        transaction: &VersionedTransaction,
+    ) -> Result<Vec<BoopSell>, PumpParseError> {
+        let signature = transaction
+            .signatures
+            .first()
+            .map(|signature| signature.to_string())
+            .unwrap_or_default();
+
+        Self::extract_instructions(transaction)
+            .iter()
+            .filter(|ix| ix.instruction_type == BoopInstructionType::SellToken)
+            .map(|ix| {
+                let args: SellTokenArgs = decode_args(&ix.data)?;
+                Ok(BoopSell {
+                    signature: signature.clone(),
+                    user: ix.pubkey(6)?,
+                    mint: ix.pubkey(0)?,
+                    amount: args.sell_amount,
+                })
+            })
+            .collect()
+    }
+}
+
+// Boop 发射台
+pub struct BoopVenue;
+
+impl Venue for BoopVenue {
+    fn kind(&self) -> VenueKind {
+        VenueKind::Boop
+    }
+
+    fn is_target_program(&self, program_id: &Pubkey) -> bool {
+        BoopParser::is_target_program(program_id)
+    }
+
+    fn detect_launch(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Option<PumpTransaction>, PumpParseError> {
+        BoopParser::parse_transaction(transaction)
+    }
+
+    fn parse_sells(&self, transaction: &VersionedTransaction) -> Vec<VenueTrade> {
+        BoopParser::parse_sells(transaction)
+            .map(|sells| {
+                sells
+                    .into_iter()
+                    .map(|sell| VenueTrade {
+                        signature: sell.signature,
+                        mint: sell.mint,
+                        user: sell.user,
+                        is_buy: false,
+                        token_amount: sell.amount,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // 按 sell_token 指令解析，不依赖事件日志格式；买入不影响退出，价格由账户订阅更新
+    fn decode_trade_events(
+        &self,
+        transaction: &VersionedTransaction,
+        _logs: &[String],
+    ) -> Vec<VenueTrade> {
+        self.parse_sells(transaction)
+    }
+
+    fn build_buy(&self, buyer: &Pubkey, buy: &LaunchBuy) -> Vec<Instruction> {
+        boop_buy_instructions(buyer, buy)
+    }
+
+    fn build_sell(&self, seller: &Pubkey, sell: &CurveSell) -> Vec<Instruction> {
+        boop_sell_instructions(seller, sell)
+    }
+
+    fn decode_curve(&self, data: &[u8]) -> Option<(CurveState, bool)> {
+        BoopBondingCurveAccount::decode(data).map(|account| (account.curve(), account.complete()))
+    }
+
+    fn fee_config(&self) -> FeeConfig {
+        boop_fee_config()
+    }
+
+    fn token_decimals(&self) -> f64 {
+        BOOP_TOKEN_DECIMALS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::{Message, VersionedMessage};
+
+    fn instruction(discriminator: [u8; 8], args: Vec<u8>, accounts: &[Pubkey]) -> Instruction {
+        let mut data = discriminator.to_vec();
+        data.extend(args);
+        Instruction::new_with_bytes(
+            BOOP_PROGRAM_ID,
+            &data,
+            accounts
+                .iter()
+                .map(|account| AccountMeta::new(*account, false))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_parse_boop_launch() {
+        let mint = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let accounts = BoopAccounts::derive(&mint);
+        let create_args = CreateTokenArgs {
+            salt: 1,
+            name: "name".to_string(),
+            symbol: "SYM".to_string(),
+            uri: "https://example.com/meta.json".to_string(),
+        };
+        let buy_args = BuyTokenArgs {
+            buy_amount: 1_000_000_000,
+            amount_out_min: 0,
+        };
+
+        let message = Message::new_with_blockhash(
+            &[
+                instruction(
+                    BOOP_CREATE_TOKEN_DISCRIMINATOR,
+                    borsh::to_vec(&create_args).unwrap(),
+                    &[mint, Pubkey::new_unique(), payer, accounts.config],
+                ),
+                instruction(
+                    BOOP_DEPLOY_BONDING_CURVE_DISCRIMINATOR,
+                    vec![],
+                    &[
+                        mint,
+                        accounts.vault_authority,
+                        accounts.bonding_curve,
+                        accounts.bonding_curve_sol_vault,
+                        accounts.bonding_curve_vault,
+                    ],
+                ),
+                instruction(
+                    BOOP_BUY_TOKEN_DISCRIMINATOR,
+                    borsh::to_vec(&buy_args).unwrap(),
+                    &[mint, accounts.bonding_curve],
+                ),
+            ],
+            Some(&payer),
+            &Hash::default(),
+        );
+        let transaction = VersionedTransaction {
+            signatures: vec![Default::default()],
+            message: VersionedMessage::Legacy(message),
+        };
+
+        let launch = BoopParser::parse_launch(&transaction, 10_000_000)
+            .unwrap()
+            .unwrap();
+        assert_eq!(launch.venue, VenueKind::Boop);
+        assert_eq!(launch.mint, mint.to_string());
+        assert_eq!(launch.creator, payer.to_string());
+        assert_eq!(launch.bonding_curve, accounts.bonding_curve.to_string());
+        assert_eq!(launch.uri, create_args.uri);
+        // 开发者买入之后价格高于初始价格
+        assert!(launch.price > BoopVenue.price(&boop_initial_curve()));
+        // 初始价格 30 SOL / 10 亿枚
+        let initial = BoopVenue.price(&boop_initial_curve());
+        assert!((initial - 30.0 / 1_000_000_000.0).abs() < 1e-15);
+        assert!(launch.my_token_amount > 0);
+    }
+
+    #[test]
+    fn test_boop_trade_events_from_sell() {
+        let mint = Pubkey::new_unique();
+        let seller = Pubkey::new_unique();
+        let accounts = BoopAccounts::derive(&mint);
+        let sell_args = SellTokenArgs {
+            sell_amount: 5_000_000,
+            amount_out_min: 0,
+        };
+
+        let message = Message::new_with_blockhash(
+            &[instruction(
+                BOOP_SELL_TOKEN_DISCRIMINATOR,
+                borsh::to_vec(&sell_args).unwrap(),
+                &[
+                    mint,
+                    accounts.bonding_curve,
+                    accounts.trading_fees_vault,
+                    accounts.bonding_curve_vault,
+                    accounts.bonding_curve_sol_vault,
+                    Pubkey::new_unique(),
+                    seller,
+                ],
+            )],
+            Some(&seller),
+            &Hash::default(),
+        );
+        let transaction = VersionedTransaction {
+            signatures: vec![Default::default()],
+            message: VersionedMessage::Legacy(message),
+        };
+
+        // gRPC 路径没有 Boop 事件日志时也能识别卖出
+        let trades = BoopVenue.decode_trade_events(&transaction, &[]);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].mint, mint);
+        assert_eq!(trades[0].user, seller);
+        assert_eq!(trades[0].token_amount, sell_args.sell_amount);
+        assert!(!trades[0].is_buy);
+    }
+}
@@ -0,0 +1,396 @@
+use anyhow::{Result, anyhow};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time;
+
+// 退出策略配置，按入场来源分配，运行时可替换
+pub const EXIT_STRATEGIES_PATH: &str = "exit_strategies.json";
+
+// 持仓的入场来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrySource {
+    #[default]
+    DevSnipe,
+    CopyTrade,
+    // 对账时在钱包里发现、未被跟踪的代币
+    Adopted,
+}
+
+// 止盈档位: 涨幅达到 gain 时卖出当前剩余持仓的 sell_fraction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TakeProfitTier {
+    pub gain: f64,
+    pub sell_fraction: f64,
+}
+
+// 移动止损: 至少完成 after_stage 档止盈后，从最高价回撤 drawdown 即清仓
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrailingStop {
+    pub after_stage: u8,
+    pub drawdown: f64,
+}
+
+// 时间止损: 持仓超过 after_ms 且涨幅低于 min_gain 时清仓
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeRule {
+    pub after_ms: u64,
+    pub min_gain: f64,
+    // 只在还没有止盈过时生效
+    #[serde(default = "default_true")]
+    pub before_first_tier: bool,
+}
+
+// 开发者行为触发
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreatorTriggers {
+    // 开发者或关联钱包卖出时紧急清仓
+    #[serde(default = "default_true")]
+    pub exit_on_dev_sell: bool,
+}
+
+impl Default for CreatorTriggers {
+    fn default() -> Self {
+        Self {
+            exit_on_dev_sell: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitStrategy {
+    pub name: String,
+    // 按涨幅从低到高排列
+    #[serde(default)]
+    pub take_profit: Vec<TakeProfitTier>,
+    #[serde(default)]
+    pub trailing_stop: Option<TrailingStop>,
+    // 跌幅达到时清仓，例如 -0.05
+    #[serde(default)]
+    pub hard_stop: Option<f64>,
+    // 触发硬止损时把开发者加入黑名单
+    #[serde(default)]
+    pub blacklist_on_stop: bool,
+    #[serde(default)]
+    pub time_rules: Vec<TimeRule>,
+    #[serde(default)]
+    pub creator: CreatorTriggers,
+}
+
+// 评估所需的持仓快照
+#[derive(Debug, Clone)]
+pub struct PositionSnapshot {
+    pub first_buy_price: f64,
+    pub price: f64,
+    pub highest_price: f64,
+    pub sell_stage: u8,
+    pub held: Option<Duration>,
+    pub balance: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExitDecision {
+    Hold,
+    Sell {
+        amount: u64,
+        // 卖出后的 sell_stage
+        stage: u8,
+        // 卖出后是否平仓
+        close: bool,
+        blacklist: bool,
+        reason: String,
+    },
+}
+
+impl ExitStrategy {
+    pub fn evaluate(&self, position: &PositionSnapshot) -> ExitDecision {
+        if position.balance == 0 || position.first_buy_price <= 0.0 {
+            return ExitDecision::Hold;
+        }
+
+        let change = (position.price - position.first_buy_price) / position.first_buy_price;
+        let close_all = |reason: String, blacklist: bool| ExitDecision::Sell {
+            amount: position.balance,
+            stage: position.sell_stage,
+            close: true,
+            blacklist,
+            reason,
+        };
+
+        if let Some(held) = position.held {
+            for rule in &self.time_rules {
+                if rule.before_first_tier && position.sell_stage > 0 {
+                    continue;
+                }
+                if held >= Duration::from_millis(rule.after_ms) && change < rule.min_gain {
+                    return close_all(
+                        format!(
+                            "持仓超过 {}ms，涨幅未达 {:.0}%",
+                            rule.after_ms,
+                            rule.min_gain * 100.0
+                        ),
+                        false,
+                    );
+                }
+            }
+        }
+
+        if let Some(trailing) = &self.trailing_stop
+            && position.sell_stage >= trailing.after_stage
+            && position.price <= position.highest_price * (1.0 - trailing.drawdown)
+        {
+            return close_all(
+                format!(
+                    "从最高价 {:.12} 回撤超 {:.0}%",
+                    position.highest_price,
+                    trailing.drawdown * 100.0
+                ),
+                false,
+            );
+        }
+
+        if let Some(tier) = self.take_profit.get(position.sell_stage as usize)
+            && change >= tier.gain
+        {
+            let stage = position.sell_stage + 1;
+            let close = tier.sell_fraction >= 1.0 || stage as usize == self.take_profit.len();
+            let amount = if close {
+                position.balance
+            } else {
+                ((position.balance as f64 * tier.sell_fraction).round() as u64).min(position.balance)
+            };
+
+            return ExitDecision::Sell {
+                amount,
+                stage,
+                close: close || amount == position.balance,
+                blacklist: false,
+                reason: format!("阶段{} 涨幅达到 {:.0}%", stage, tier.gain * 100.0),
+            };
+        }
+
+        if let Some(hard_stop) = self.hard_stop
+            && (position.sell_stage as usize) < self.take_profit.len().max(1)
+            && change <= hard_stop
+        {
+            return close_all(
+                format!("跌幅达到 {:.0}%，触发止损", hard_stop * 100.0),
+                self.blacklist_on_stop,
+            );
+        }
+
+        ExitDecision::Hold
+    }
+}
+
+// 原先写死在 update_price_and_maybe_sell 里的阶梯
+pub fn default_strategy() -> ExitStrategy {
+    ExitStrategy {
+        name: "default".to_string(),
+        take_profit: vec![
+            TakeProfitTier {
+                gain: 0.20,
+                sell_fraction: 0.5,
+            },
+            TakeProfitTier {
+                gain: 0.40,
+                sell_fraction: 0.4,
+            },
+            TakeProfitTier {
+                gain: 0.60,
+                sell_fraction: 1.0,
+            },
+        ],
+        trailing_stop: Some(TrailingStop {
+            after_stage: 1,
+            drawdown: 0.05,
+        }),
+        hard_stop: Some(-0.05),
+        blacklist_on_stop: true,
+        time_rules: vec![TimeRule {
+            after_ms: 2000,
+            min_gain: 0.20,
+            before_first_tier: true,
+        }],
+        creator: CreatorTriggers::default(),
+    }
+}
+
+// exit_strategies.json，例如:
+// {"strategies": [{"name": "slow", "take_profit": [{"gain": 1.0, "sell_fraction": 1.0}], "hard_stop": -0.3}],
+//  "assignments": {"dev_snipe": "default", "copy_trade": "slow"}}
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExitStrategyConfig {
+    #[serde(default)]
+    pub strategies: Vec<ExitStrategy>,
+    #[serde(default)]
+    pub assignments: HashMap<EntrySource, String>,
+}
+
+impl ExitStrategyConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for strategy in &self.strategies {
+            for tier in &strategy.take_profit {
+                if !(0.0..=1.0).contains(&tier.sell_fraction) || tier.sell_fraction == 0.0 {
+                    return Err(format!("{} 的 sell_fraction 必须在 (0, 1] 之间", strategy.name));
+                }
+            }
+            if strategy.take_profit.windows(2).any(|w| w[0].gain >= w[1].gain) {
+                return Err(format!("{} 的止盈档位必须按涨幅递增", strategy.name));
+            }
+        }
+
+        for name in self.assignments.values() {
+            if name != "default" && !self.strategies.iter().any(|s| &s.name == name) {
+                return Err(format!("未定义的策略 {}", name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+static EXIT_STRATEGY_CONFIG: Lazy<RwLock<ExitStrategyConfig>> =
+    Lazy::new(|| RwLock::new(ExitStrategyConfig::default()));
+
+// 按名称缓存，避免每次价格更新都克隆整个配置
+static STRATEGY_CACHE: Lazy<DashMap<EntrySource, Arc<ExitStrategy>>> = Lazy::new(DashMap::new);
+
+pub fn exit_strategy_config() -> ExitStrategyConfig {
+    EXIT_STRATEGY_CONFIG.read().unwrap().clone()
+}
+
+pub fn set_exit_strategy_config(config: ExitStrategyConfig) -> Result<(), String> {
+    config.validate()?;
+    *EXIT_STRATEGY_CONFIG.write().unwrap() = config;
+    STRATEGY_CACHE.clear();
+    Ok(())
+}
+
+// 入场来源对应的策略，未分配时使用默认阶梯
+pub fn exit_strategy_for(source: EntrySource) -> Arc<ExitStrategy> {
+    if let Some(strategy) = STRATEGY_CACHE.get(&source) {
+        return strategy.clone();
+    }
+
+    let config = EXIT_STRATEGY_CONFIG.read().unwrap();
+    let strategy = config
+        .assignments
+        .get(&source)
+        .and_then(|name| config.strategies.iter().find(|s| &s.name == name))
+        .cloned()
+        .unwrap_or_else(default_strategy);
+    let strategy = Arc::new(strategy);
+    STRATEGY_CACHE.insert(source, strategy.clone());
+    strategy
+}
+
+// 配置接口更新，同时写回文件，持仓立即按新策略执行
+pub async fn update_exit_strategies(config: ExitStrategyConfig) -> Result<()> {
+    config.validate().map_err(|e| anyhow!(e))?;
+    let content = serde_json::to_string_pretty(&config)?;
+    tokio::fs::write(EXIT_STRATEGIES_PATH, content).await?;
+
+    set_exit_strategy_config(config).map_err(|e| anyhow!(e))
+}
+
+pub async fn watch_exit_strategies(path: &'static str) {
+    loop {
+        if let Ok(content) = tokio::fs::read_to_string(path).await {
+            match serde_json::from_str::<ExitStrategyConfig>(&content) {
+                Ok(config) => {
+                    if config != exit_strategy_config() {
+                        match set_exit_strategy_config(config) {
+                            Ok(()) => println!("[退出策略] 已更新"),
+                            Err(e) => println!("[退出策略] {} 无效: {}", path, e),
+                        }
+                    }
+                }
+                Err(e) => println!("[退出策略] 解析 {} 失败: {:?}", path, e),
+            }
+        }
+
+        time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(price: f64, highest_price: f64, sell_stage: u8, held_ms: u64) -> PositionSnapshot {
+        PositionSnapshot {
+            first_buy_price: 1.0,
+            price,
+            highest_price,
+            sell_stage,
+            held: Some(Duration::from_millis(held_ms)),
+            balance: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_default_ladder() {
+        let strategy = default_strategy();
+
+        assert_eq!(strategy.evaluate(&position(1.1, 1.1, 0, 500)), ExitDecision::Hold);
+
+        // 阶段1 卖出一半
+        let ExitDecision::Sell { amount, stage, close, .. } =
+            strategy.evaluate(&position(1.25, 1.25, 0, 500))
+        else {
+            panic!("expected sell");
+        };
+        assert_eq!((amount, stage, close), (500, 1, false));
+
+        // 2 秒未涨到 20% 清仓
+        let ExitDecision::Sell { amount, close, .. } =
+            strategy.evaluate(&position(1.1, 1.1, 0, 2500))
+        else {
+            panic!("expected sell");
+        };
+        assert_eq!((amount, close), (1_000, true));
+
+        // 止盈后回撤 5% 清仓，时间止损不再生效
+        assert!(matches!(
+            strategy.evaluate(&position(1.3, 1.4, 1, 5000)),
+            ExitDecision::Sell { close: true, .. }
+        ));
+        assert_eq!(strategy.evaluate(&position(1.38, 1.4, 1, 5000)), ExitDecision::Hold);
+
+        // 硬止损并拉黑
+        assert!(matches!(
+            strategy.evaluate(&position(0.9, 1.0, 0, 100)),
+            ExitDecision::Sell { blacklist: true, close: true, .. }
+        ));
+
+        // 最后一档全部卖出
+        assert!(matches!(
+            strategy.evaluate(&position(1.7, 1.7, 2, 5000)),
+            ExitDecision::Sell { stage: 3, close: true, .. }
+        ));
+    }
+
+    #[test]
+    fn test_validate_assignments() {
+        let mut config = ExitStrategyConfig::default();
+        config
+            .assignments
+            .insert(EntrySource::CopyTrade, "missing".to_string());
+        assert!(config.validate().is_err());
+
+        config.strategies.push(ExitStrategy {
+            name: "missing".to_string(),
+            ..default_strategy()
+        });
+        assert!(config.validate().is_ok());
+    }
+}
@@ -1,4 +1,12 @@
+pub mod bonding_curve;
+pub mod boop_parser;
+pub mod exit_strategy;
+pub mod pump_amm;
+pub mod pump_idl;
 pub mod pump_parser;
+pub mod raydium_amm;
 pub mod transaction;
-pub use pump_parser::{PumpParser, PumpTransaction};
+pub mod venue;
+pub mod wrapper_registry;
+pub use pump_parser::{PumpBuy, PumpParser, PumpSell, PumpTransaction};
 pub use transaction::TransactionResults;
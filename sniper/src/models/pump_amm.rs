@@ -0,0 +1,158 @@
+use crate::models::bonding_curve::FEE_DENOMINATOR;
+use crate::transaction::PUMP_PROGRAM_ID;
+use anyhow::{Result, anyhow};
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+
+// 迁移后的 PumpSwap 池子，bonding curve 完成后代币在这里交易
+pub const PUMP_AMM_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+pub const PUMP_AMM_GLOBAL_CONFIG: Pubkey =
+    solana_sdk::pubkey!("ADyA8hdefvWN2dbGGWFotbzWxrAvLW83WG6QCVXvJKqw");
+pub const PUMP_AMM_EVENT_AUTHORITY: Pubkey =
+    solana_sdk::pubkey!("GS4CU59F31iL7aR2Q8zVS8DRrcRnXX1yjQ66TqNVQnaR");
+
+pub const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+pub const GLOBAL_CONFIG_DISCRIMINATOR: [u8; 8] = [149, 8, 156, 202, 160, 252, 176, 217];
+
+// Pool 账户，只解码需要的前缀字段
+#[derive(Debug, Clone, PartialEq, BorshDeserialize)]
+pub struct PoolAccount {
+    pub pool_bump: u8,
+    pub index: u16,
+    pub creator: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    pub lp_supply: u64,
+    pub coin_creator: Pubkey,
+}
+
+impl PoolAccount {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != POOL_DISCRIMINATOR {
+            return Err(anyhow!("Pool 账户 discriminator 不匹配"));
+        }
+
+        let mut rest = &data[8..];
+        Ok(Self::deserialize(&mut rest)?)
+    }
+}
+
+// PumpSwap GlobalConfig: 手续费和协议收费地址
+#[derive(Debug, Clone, PartialEq, BorshDeserialize)]
+pub struct AmmGlobalConfig {
+    pub admin: Pubkey,
+    pub lp_fee_basis_points: u64,
+    pub protocol_fee_basis_points: u64,
+    pub disable_flags: u8,
+    pub protocol_fee_recipients: [Pubkey; 8],
+    pub coin_creator_fee_basis_points: u64,
+}
+
+impl AmmGlobalConfig {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != GLOBAL_CONFIG_DISCRIMINATOR {
+            return Err(anyhow!("GlobalConfig 账户 discriminator 不匹配"));
+        }
+
+        let mut rest = &data[8..];
+        Ok(Self::deserialize(&mut rest)?)
+    }
+
+    pub fn protocol_fee_recipient(&self) -> Option<Pubkey> {
+        self.protocol_fee_recipients
+            .iter()
+            .find(|recipient| **recipient != Pubkey::default())
+            .copied()
+    }
+}
+
+// 迁移创建的池子: pool-authority 由 PUMP 程序派生，index 固定为 0，报价币为 WSOL
+pub fn canonical_pool(mint: &Pubkey) -> Pubkey {
+    let (pool_authority, _) =
+        Pubkey::find_program_address(&[b"pool-authority", mint.as_ref()], &PUMP_PROGRAM_ID);
+    let (pool, _) = Pubkey::find_program_address(
+        &[
+            b"pool",
+            &0u16.to_le_bytes(),
+            pool_authority.as_ref(),
+            mint.as_ref(),
+            spl_token::native_mint::id().as_ref(),
+        ],
+        &PUMP_AMM_PROGRAM_ID,
+    );
+    pool
+}
+
+pub fn coin_creator_vault_authority(coin_creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"creator_vault", coin_creator.as_ref()],
+        &PUMP_AMM_PROGRAM_ID,
+    )
+    .0
+}
+
+// 池子储备和手续费，按恒定乘积报价
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolState {
+    pub base_reserves: u64,
+    pub quote_reserves: u64,
+    pub lp_fee_basis_points: u64,
+    pub protocol_fee_basis_points: u64,
+    // 没有设置 coin_creator 的池子不收创作者费
+    pub coin_creator_fee_basis_points: u64,
+}
+
+impl PoolState {
+    // 卖出 base_amount 实际到手的 SOL（已扣手续费），手续费从输出中向上取整扣除
+    pub fn sell_quote(&self, base_amount: u64) -> u64 {
+        if base_amount == 0 || self.base_reserves == 0 {
+            return 0;
+        }
+
+        let quote_out = base_amount as u128 * self.quote_reserves as u128
+            / (self.base_reserves as u128 + base_amount as u128);
+        let fee = |bps: u64| (quote_out * bps as u128).div_ceil(FEE_DENOMINATOR);
+        let fees = fee(self.lp_fee_basis_points)
+            + fee(self.protocol_fee_basis_points)
+            + fee(self.coin_creator_fee_basis_points);
+
+        quote_out.saturating_sub(fees) as u64
+    }
+
+    pub fn sell_quote_with_slippage(&self, base_amount: u64, slippage_bps: u64) -> u64 {
+        let quoted = self.sell_quote(base_amount) as u128;
+        (quoted * FEE_DENOMINATOR.saturating_sub(slippage_bps as u128) / FEE_DENOMINATOR) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_sell_quote() {
+        let pool = PoolState {
+            base_reserves: 200_000_000_000_000,
+            quote_reserves: 85_000_000_000,
+            lp_fee_basis_points: 20,
+            protocol_fee_basis_points: 5,
+            coin_creator_fee_basis_points: 5,
+        };
+
+        // 卖出 1% 的储备: 85 SOL * 1 / 101 ≈ 0.8416 SOL，扣 0.3% 手续费
+        let quote_out = 85_000_000_000u128 * 2_000_000_000_000 / 202_000_000_000_000;
+        let quoted = pool.sell_quote(2_000_000_000_000);
+        assert!(quoted < quote_out as u64);
+        assert!(quoted >= (quote_out * 9970 / 10_000) as u64 - 3);
+
+        assert_eq!(
+            pool.sell_quote_with_slippage(2_000_000_000_000, 1000),
+            (quoted as u128 * 9 / 10) as u64
+        );
+        assert_eq!(pool.sell_quote(0), 0);
+    }
+}
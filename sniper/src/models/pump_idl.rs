@@ -0,0 +1,89 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+
+// PUMP IDL 指令参数，数据布局: 8 字节 discriminator + borsh 编码的参数
+
+// create(name, symbol, uri, creator)
+#[derive(Debug, Clone, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct CreateArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub creator: Pubkey,
+}
+
+// buy(amount, max_sol_cost)
+#[derive(Debug, Clone, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct BuyArgs {
+    pub amount: u64,
+    pub max_sol_cost: u64,
+}
+
+// sell(amount, min_sol_output)
+#[derive(Debug, Clone, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct SellArgs {
+    pub amount: u64,
+    pub min_sol_output: u64,
+}
+
+#[derive(Debug)]
+pub enum PumpParseError {
+    // 指令数据不足 8 字节 discriminator
+    MissingDiscriminator,
+    // 指令类型与期望的参数不符
+    UnexpectedInstruction,
+    // 参数 borsh 解码失败
+    InvalidArgs(std::io::Error),
+    // 指令缺少指定下标的账户
+    MissingAccount(usize),
+}
+
+impl fmt::Display for PumpParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PumpParseError::MissingDiscriminator => write!(f, "指令数据缺少 discriminator"),
+            PumpParseError::UnexpectedInstruction => write!(f, "指令类型不匹配"),
+            PumpParseError::InvalidArgs(e) => write!(f, "指令参数解码失败: {}", e),
+            PumpParseError::MissingAccount(idx) => write!(f, "指令缺少账户 [{}]", idx),
+        }
+    }
+}
+
+impl std::error::Error for PumpParseError {}
+
+// 解码 discriminator 之后的参数；新版程序可能在末尾追加可选参数，所以不要求读完全部数据
+pub fn decode_args<T: BorshDeserialize>(data: &[u8]) -> Result<T, PumpParseError> {
+    let mut args = data.get(8..).ok_or(PumpParseError::MissingDiscriminator)?;
+    T::deserialize(&mut args).map_err(PumpParseError::InvalidArgs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_create_args() {
+        let args = CreateArgs {
+            name: "name".to_string(),
+            symbol: "SYM".to_string(),
+            uri: "https://example.com/meta.json".to_string(),
+            creator: Pubkey::new_unique(),
+        };
+
+        let mut data = vec![0u8; 8];
+        data.extend(borsh::to_vec(&args).unwrap());
+        assert_eq!(decode_args::<CreateArgs>(&data).unwrap(), args);
+
+        // 截断的 uri 应返回解码错误而不是空字符串
+        data.truncate(8 + 4 + 4 + 4 + 3 + 4 + 5);
+        assert!(matches!(
+            decode_args::<CreateArgs>(&data),
+            Err(PumpParseError::InvalidArgs(_))
+        ));
+        assert!(matches!(
+            decode_args::<BuyArgs>(&[1, 2, 3]),
+            Err(PumpParseError::MissingDiscriminator)
+        ));
+    }
+}
@@ -1,17 +1,32 @@
-use crate::config::MAX_SOL;
+use crate::config::{BUY_SLIPPAGE_BPS, MAX_SOL};
+use crate::models::bonding_curve::{
+    BondingCurveAccount, CurveState, FeeConfig, LAMPORTS_PER_SOL, TOKEN_DECIMALS,
+    current_fee_config, update_fee_config,
+};
+use crate::models::pump_idl::{BuyArgs, CreateArgs, PumpParseError, SellArgs, decode_args};
+use crate::models::venue::{CurveSell, LaunchBuy, Venue, VenueKind, VenueTrade, venue};
+use crate::monitor::{EventTrait, TradeEvent};
+use crate::models::wrapper_registry;
+use crate::services::alt_cache;
+use crate::transaction::{pump_buy_instructions, pump_sell_instructions};
 use solana_program::instruction::CompiledInstruction;
+use solana_sdk::instruction::Instruction;
 use solana_sdk::message::VersionedMessage;
-use solana_sdk::message::legacy::Message as LegacyMessage;
-use solana_sdk::message::v0::Message as V0Message;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::transaction::VersionedTransaction;
+use std::borrow::Cow;
 use std::fmt;
 use std::str::FromStr;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 // PUMP程序ID
 #[allow(dead_code)]
 pub const PUMP_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+const PUMP_PROGRAM_PUBKEY: Pubkey = solana_sdk::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+
+// PUMP指令discriminator
+pub const PUMP_BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+pub const PUMP_CREATE_DISCRIMINATOR: [u8; 8] = [24, 30, 200, 40, 5, 28, 7, 119];
+pub const PUMP_SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
 
 // PUMP指令类型
 #[derive(Debug, PartialEq, Clone)]
@@ -20,6 +35,7 @@ pub enum PumpInstructionType {
     Unknown,
     Buy,    // 只关注Buy指令
     Create, // 只关注Create指令
+    Sell,   // 用于识别开发者砸盘
 }
 
 // PUMP指令的详细信息
@@ -30,195 +46,123 @@ pub struct PumpInstruction {
     pub data: Vec<u8>,
 }
 
+impl PumpInstruction {
+    // 解码Create指令参数
+    pub fn create_args(&self) -> Result<CreateArgs, PumpParseError> {
+        if self.instruction_type != PumpInstructionType::Create {
+            return Err(PumpParseError::UnexpectedInstruction);
+        }
+        decode_args(&self.data)
+    }
+
+    // 解码Buy指令参数
+    pub fn buy_args(&self) -> Result<BuyArgs, PumpParseError> {
+        if self.instruction_type != PumpInstructionType::Buy {
+            return Err(PumpParseError::UnexpectedInstruction);
+        }
+        decode_args(&self.data)
+    }
+
+    // 解码Sell指令参数
+    pub fn sell_args(&self) -> Result<SellArgs, PumpParseError> {
+        if self.instruction_type != PumpInstructionType::Sell {
+            return Err(PumpParseError::UnexpectedInstruction);
+        }
+        decode_args(&self.data)
+    }
+
+    // 按下标取账户地址
+    pub fn account(&self, idx: usize) -> Result<&str, PumpParseError> {
+        self.accounts
+            .get(idx)
+            .map(String::as_str)
+            .ok_or(PumpParseError::MissingAccount(idx))
+    }
+
+    // 按下标取账户并转换为 Pubkey
+    pub fn pubkey(&self, idx: usize) -> Result<Pubkey, PumpParseError> {
+        let account = self.account(idx)?;
+        Pubkey::from_str(account).map_err(|_| PumpParseError::MissingAccount(idx))
+    }
+
+    fn write_accounts(&self, f: &mut fmt::Formatter<'_>, labels: &[&str]) -> fmt::Result {
+        for (idx, (account, label)) in self.accounts.iter().zip(labels).enumerate() {
+            writeln!(f, "[{}]{}: {}", idx, label, account)?;
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Display for PumpInstruction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.instruction_type {
             PumpInstructionType::Buy => {
-                if self.data.len() >= 16 {
-                    // 解析amount参数
-                    let amount = u64::from_le_bytes([
-                        self.data[8],
-                        self.data[9],
-                        self.data[10],
-                        self.data[11],
-                        self.data[12],
-                        self.data[13],
-                        self.data[14],
-                        self.data[15],
-                    ]);
-
-                    writeln!(f, "Token_Amount: {}", amount)?;
-
-                    // 解析max_sol_cost参数
-                    if self.data.len() >= 24 {
-                        let max_sol_cost = u64::from_le_bytes([
-                            self.data[16],
-                            self.data[17],
-                            self.data[18],
-                            self.data[19],
-                            self.data[20],
-                            self.data[21],
-                            self.data[22],
-                            self.data[23],
-                        ]);
-                        writeln!(f, "Max_SOL_Cost: {} ", max_sol_cost)?;
+                match self.buy_args() {
+                    Ok(args) => {
+                        writeln!(f, "Token_Amount: {}", args.amount)?;
+                        writeln!(f, "Max_SOL_Cost: {} ", args.max_sol_cost)?;
                     }
+                    Err(e) => writeln!(f, "Buy参数解析失败: {}", e)?,
                 }
 
                 // 打印账户信息
-                if self.accounts.len() >= 3 {
-                    writeln!(f, "[0]Global: {}", self.accounts[0])?;
-                    writeln!(f, "[1]Fee_Recipient: {}", self.accounts[1])?;
-                    writeln!(f, "[2]Mint: {}", self.accounts[2])?;
-
-                    if self.accounts.len() >= 4 {
-                        writeln!(f, "[3]Bonding_Curve: {}", self.accounts[3])?;
-                    }
-                    if self.accounts.len() >= 5 {
-                        writeln!(f, "[4]Associated_Bonding_Curve: {}", self.accounts[4])?;
-                    }
-                    if self.accounts.len() >= 6 {
-                        writeln!(f, "[5]Associated_User: {}", self.accounts[5])?;
-                    }
-                    if self.accounts.len() >= 7 {
-                        writeln!(f, "[6]User: {}", self.accounts[6])?;
-                    }
-                    if self.accounts.len() >= 8 {
-                        writeln!(f, "[7]System_Program: {}", self.accounts[7])?;
-                    }
-                    if self.accounts.len() >= 9 {
-                        writeln!(f, "[8]Token_Program: {}", self.accounts[8])?;
-                    }
-                    if self.accounts.len() >= 10 {
-                        writeln!(f, "[9]Rent: {}", self.accounts[9])?;
-                    }
-                    if self.accounts.len() >= 11 {
-                        writeln!(f, "[10]Event_Authority: {}", self.accounts[10])?;
-                    }
-                    if self.accounts.len() >= 12 {
-                        writeln!(f, "[11]Program: {}", self.accounts[11])?;
-                    }
-                }
+                self.write_accounts(
+                    f,
+                    &[
+                        "Global",
+                        "Fee_Recipient",
+                        "Mint",
+                        "Bonding_Curve",
+                        "Associated_Bonding_Curve",
+                        "Associated_User",
+                        "User",
+                        "System_Program",
+                        "Token_Program",
+                        "Rent",
+                        "Event_Authority",
+                        "Program",
+                    ],
+                )?;
             }
             PumpInstructionType::Create => {
-                // Create指令的字符串参数在data[8..]之后
-                if self.data.len() > 8 {
-                    // 前8字节是discriminator，后面是参数数据
-                    let mut offset = 8;
-
-                    // 解析name字段
-                    if offset + 4 <= self.data.len() {
-                        // 读取name字符串长度
-                        let name_len = u32::from_le_bytes([
-                            self.data[offset],
-                            self.data[offset + 1],
-                            self.data[offset + 2],
-                            self.data[offset + 3],
-                        ]) as usize;
-                        offset += 4;
-
-                        // 读取name字符串内容
-                        if offset + name_len <= self.data.len() {
-                            let name =
-                                String::from_utf8_lossy(&self.data[offset..offset + name_len]);
-                            writeln!(f, "name: {}", name)?;
-                            offset += name_len;
-
-                            // 解析symbol字段
-                            if offset + 4 <= self.data.len() {
-                                // 读取symbol字符串长度
-                                let symbol_len = u32::from_le_bytes([
-                                    self.data[offset],
-                                    self.data[offset + 1],
-                                    self.data[offset + 2],
-                                    self.data[offset + 3],
-                                ]) as usize;
-                                offset += 4;
-
-                                // 读取symbol字符串内容
-                                if offset + symbol_len <= self.data.len() {
-                                    let symbol = String::from_utf8_lossy(
-                                        &self.data[offset..offset + symbol_len],
-                                    );
-                                    writeln!(f, "symbol: {}", symbol)?;
-                                    offset += symbol_len;
-
-                                    // 解析URI字段
-                                    if offset + 4 <= self.data.len() {
-                                        // 读取URI字符串长度
-                                        let uri_len = u32::from_le_bytes([
-                                            self.data[offset],
-                                            self.data[offset + 1],
-                                            self.data[offset + 2],
-                                            self.data[offset + 3],
-                                        ])
-                                            as usize;
-                                        offset += 4;
-
-                                        // 读取URI字符串内容
-                                        if offset + uri_len <= self.data.len() {
-                                            let uri = String::from_utf8_lossy(
-                                                &self.data[offset..offset + uri_len],
-                                            );
-                                            writeln!(f, "uri: {}", uri)?;
-                                            offset += uri_len;
-
-                                            // 解析creator字段 (Pubkey是32字节)
-                                            if offset + 32 <= self.data.len() {
-                                                let creator =
-                                                    bs58::encode(&self.data[offset..offset + 32])
-                                                        .into_string();
-                                                writeln!(f, "creator: {}", creator)?;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                match self.create_args() {
+                    Ok(args) => {
+                        writeln!(f, "name: {}", args.name)?;
+                        writeln!(f, "symbol: {}", args.symbol)?;
+                        writeln!(f, "uri: {}", args.uri)?;
+                        writeln!(f, "creator: {}", args.creator)?;
                     }
+                    Err(e) => writeln!(f, "Create参数解析失败: {}", e)?,
                 }
 
                 // 打印账户信息
-                if self.accounts.len() >= 3 {
-                    writeln!(f, "[0]Mint: {}", self.accounts[0])?;
-                    writeln!(f, "[1]Mint_Authority: {}", self.accounts[1])?;
-                    writeln!(f, "[2]Bonding_Curve: {}", self.accounts[2])?;
-
-                    if self.accounts.len() >= 4 {
-                        writeln!(f, "[3]Associated_Bonding_Curve: {}", self.accounts[3])?;
-                    }
-                    if self.accounts.len() >= 5 {
-                        writeln!(f, "[4]Global: {}", self.accounts[4])?;
-                    }
-                    if self.accounts.len() >= 6 {
-                        writeln!(f, "[5]Mpl_Token_Metadata: {}", self.accounts[5])?;
-                    }
-                    if self.accounts.len() >= 7 {
-                        writeln!(f, "[6]Metadata: {}", self.accounts[6])?;
-                    }
-                    if self.accounts.len() >= 8 {
-                        writeln!(f, "[7]User: {}", self.accounts[7])?;
-                    }
-                    if self.accounts.len() >= 9 {
-                        writeln!(f, "[8]System_Program: {}", self.accounts[8])?;
-                    }
-                    if self.accounts.len() >= 10 {
-                        writeln!(f, "[9]Token_Program: {}", self.accounts[9])?;
-                    }
-                    if self.accounts.len() >= 11 {
-                        writeln!(f, "[10]Associated_Token_Program: {}", self.accounts[10])?;
-                    }
-                    if self.accounts.len() >= 12 {
-                        writeln!(f, "[11]Rent: {}", self.accounts[11])?;
-                    }
-                    if self.accounts.len() >= 13 {
-                        writeln!(f, "[12]Event_Authority: {}", self.accounts[12])?;
-                    }
-                    if self.accounts.len() >= 14 {
-                        writeln!(f, "[13]Program: {}", self.accounts[13])?;
-                    }
-                }
+                self.write_accounts(
+                    f,
+                    &[
+                        "Mint",
+                        "Mint_Authority",
+                        "Bonding_Curve",
+                        "Associated_Bonding_Curve",
+                        "Global",
+                        "Mpl_Token_Metadata",
+                        "Metadata",
+                        "User",
+                        "System_Program",
+                        "Token_Program",
+                        "Associated_Token_Program",
+                        "Rent",
+                        "Event_Authority",
+                        "Program",
+                    ],
+                )?;
             }
+            PumpInstructionType::Sell => match self.sell_args() {
+                Ok(args) => {
+                    writeln!(f, "Token_Amount: {}", args.amount)?;
+                    writeln!(f, "Min_SOL_Output: {} ", args.min_sol_output)?;
+                }
+                Err(e) => writeln!(f, "Sell参数解析失败: {}", e)?,
+            },
             PumpInstructionType::Unknown => {
                 // 不打印未知指令的详细信息
             }
@@ -228,306 +172,119 @@ impl fmt::Display for PumpInstruction {
     }
 }
 
-// PUMP交易的解析结果
-#[derive(Debug, Clone)]
+// 每次狙击投入的 SOL (lamports)
+pub(crate) fn snipe_budget_lamports() -> u64 {
+    unsafe { (*MAX_SOL * LAMPORTS_PER_SOL) as u64 }
+}
+
+// 发币交易的解析结果，Boop 发币也用同一结构，venue 区分
+#[derive(Debug, Clone, Default)]
 pub struct PumpTransaction {
+    // 发币所在的发射台，决定买卖指令和报价方式
+    pub venue: VenueKind,
     pub signature: String,
     pub mint: String,
     pub bonding_curve: String,
     pub associated_bonding_curve: String,
     pub creator: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
     pub price: f64,
     pub buy_amount: u64,
     pub max_sol_cost: u64,
     pub my_token_amount: u64,
+    // 预计我们的买入成交时的曲线
+    pub curve: CurveState,
     pub instructions: Vec<PumpInstruction>,
 }
 
-// 添加用于计算债券曲线的结构体
-#[derive(Debug, Clone)]
-pub struct TokenReserves {
-    pub virtual_sol_reserves: u64,   // 虚拟SOL储备
-    pub virtual_token_reserves: u64, // 虚拟代币储备
-}
-
-impl TokenReserves {
-    // 计算当前价格 (SOL/Token)，考虑代币精度
-    pub fn calculate_price(&self) -> f64 {
-        if self.virtual_token_reserves == 0 {
-            return 0.0;
+impl PumpTransaction {
+    // 同一批次中该 mint 的其他买入排在我们前面成交，按它们之后的曲线重新报价
+    pub fn apply_batch_buys(&mut self, amounts: impl IntoIterator<Item = u64>) {
+        let curve = amounts
+            .into_iter()
+            .fold(self.curve, |curve, amount| curve.after_buy(amount));
+        if curve != self.curve {
+            self.set_curve(curve);
         }
-
-        // 直接计算实际价格比例，不考虑精度因子
-        // 这样显示的是原始比例，便于查看实际数值
-        (self.virtual_sol_reserves as f64) / (self.virtual_token_reserves as f64)
     }
 
-    // 计算购买指定数量代币所需的SOL
-    pub fn calculate_sol_cost(&self, token_amount: u64) -> f64 {
-        if token_amount == 0 || self.virtual_token_reserves == 0 {
-            return 0.0;
-        }
-
-        let k = (self.virtual_sol_reserves as f64) * (self.virtual_token_reserves as f64);
-        let new_token_reserves = self.virtual_token_reserves.saturating_sub(token_amount) as f64;
-        if new_token_reserves <= 0.0 {
-            return f64::MAX;
-        }
-
-        let new_sol_reserves = k / new_token_reserves;
-        new_sol_reserves - (self.virtual_sol_reserves as f64)
+    pub(crate) fn set_curve(&mut self, curve: CurveState) {
+        self.set_curve_with_budget(curve, snipe_budget_lamports());
     }
 
-    // 计算销售指定数量代币获得的SOL
-    pub fn calculate_sol_return(&self, token_amount: u64) -> f64 {
-        if token_amount == 0 {
-            return 0.0;
-        }
-
-        let k = (self.virtual_sol_reserves as f64) * (self.virtual_token_reserves as f64);
-        let new_token_reserves = (self.virtual_token_reserves as f64) + (token_amount as f64);
-        let new_sol_reserves = k / new_token_reserves;
+    // 按指定的买入 SOL 报价，测试中不依赖 MAX_SOL 环境变量
+    pub(crate) fn set_curve_with_budget(&mut self, curve: CurveState, max_sol_lamports: u64) {
+        self.curve = curve;
+        self.price = venue(self.venue).price(&curve);
+        self.my_token_amount = curve.buy_quote_with_slippage(
+            max_sol_lamports,
+            &venue(self.venue).fee_config(),
+            *BUY_SLIPPAGE_BPS,
+        );
+    }
 
-        (self.virtual_sol_reserves as f64) - new_sol_reserves
+    // 发币交易中买入的钱包（不含开发者本人时即为关联钱包）
+    pub fn buyers(&self) -> Vec<Pubkey> {
+        self.instructions
+            .iter()
+            .filter(|ix| ix.instruction_type == PumpInstructionType::Buy)
+            .filter_map(|ix| ix.pubkey(6).ok())
+            .filter(|user| user.to_string() != self.creator)
+            .collect()
     }
 }
 
 impl fmt::Display for PumpTransaction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // 检查是否有Create指令和Buy指令（这个检查在transaction_processor中已经提前完成）
-        // 提前获取指令引用，避免重复迭代
-        let mut create_instructions = Vec::with_capacity(1);
-        let mut buy_instructions = Vec::with_capacity(1);
-
-        for ix in &self.instructions {
-            match ix.instruction_type {
-                PumpInstructionType::Create => create_instructions.push(ix),
-                PumpInstructionType::Buy => buy_instructions.push(ix),
-                _ => {}
-            }
-        }
-
-        // 如果没有足够的指令类型，提前返回
-        if create_instructions.is_empty() || buy_instructions.is_empty() {
-            return Ok(());
-        }
-
-        // 假设现在只有一个Create指令和一个Buy指令
-        let create_ix = &create_instructions[0];
-        let buy_ix = &buy_instructions[0];
-
-        // 从Create指令中提取元数据信息
-        let mut name = String::new();
-        let mut symbol = String::new();
-        let mut uri = String::new();
-        let mut creator = String::new();
-        let mut mint = String::new();
-        let mut bonding_curve = String::new();
-        let mut associated_boding_curve = String::new();
-        let mut user_address = String::new();
-
-        // 提取元数据
-        if create_ix.data.len() > 8 {
-            let mut offset = 8;
-
-            // 读取name
-            if offset + 4 <= create_ix.data.len() {
-                let name_len = u32::from_le_bytes([
-                    create_ix.data[offset],
-                    create_ix.data[offset + 1],
-                    create_ix.data[offset + 2],
-                    create_ix.data[offset + 3],
-                ]) as usize;
-                offset += 4;
-
-                if offset + name_len <= create_ix.data.len() {
-                    name = String::from_utf8_lossy(&create_ix.data[offset..offset + name_len])
-                        .to_string();
-                    offset += name_len;
-
-                    // 读取symbol
-                    if offset + 4 <= create_ix.data.len() {
-                        let symbol_len = u32::from_le_bytes([
-                            create_ix.data[offset],
-                            create_ix.data[offset + 1],
-                            create_ix.data[offset + 2],
-                            create_ix.data[offset + 3],
-                        ]) as usize;
-                        offset += 4;
-
-                        if offset + symbol_len <= create_ix.data.len() {
-                            symbol = String::from_utf8_lossy(
-                                &create_ix.data[offset..offset + symbol_len],
-                            )
-                            .to_string();
-                            offset += symbol_len;
-
-                            // 读取URI
-                            if offset + 4 <= create_ix.data.len() {
-                                let uri_len = u32::from_le_bytes([
-                                    create_ix.data[offset],
-                                    create_ix.data[offset + 1],
-                                    create_ix.data[offset + 2],
-                                    create_ix.data[offset + 3],
-                                ]) as usize;
-                                offset += 4;
-
-                                if offset + uri_len <= create_ix.data.len() {
-                                    uri = String::from_utf8_lossy(
-                                        &create_ix.data[offset..offset + uri_len],
-                                    )
-                                    .to_string();
-                                    offset += uri_len;
-
-                                    // 读取creator
-                                    if offset + 32 <= create_ix.data.len() {
-                                        creator =
-                                            bs58::encode(&create_ix.data[offset..offset + 32])
-                                                .into_string();
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        // 提取Mint、BondingCurve和User地址
-        if create_ix.accounts.len() >= 3 {
-            mint = create_ix.accounts[0].clone();
-            bonding_curve = create_ix.accounts[2].clone();
-            associated_boding_curve = create_ix.accounts[3].clone();
-
-            if create_ix.accounts.len() >= 8 {
-                user_address = create_ix.accounts[7].clone();
-            }
-        }
-
-        // 从Buy指令中提取交易信息
-        let mut token_amount = 0u64;
-        let mut max_sol_cost = 0u64;
-
-        if buy_ix.data.len() >= 16 {
-            token_amount = u64::from_le_bytes([
-                buy_ix.data[8],
-                buy_ix.data[9],
-                buy_ix.data[10],
-                buy_ix.data[11],
-                buy_ix.data[12],
-                buy_ix.data[13],
-                buy_ix.data[14],
-                buy_ix.data[15],
-            ]);
-
-            if buy_ix.data.len() >= 24 {
-                max_sol_cost = u64::from_le_bytes([
-                    buy_ix.data[16],
-                    buy_ix.data[17],
-                    buy_ix.data[18],
-                    buy_ix.data[19],
-                    buy_ix.data[20],
-                    buy_ix.data[21],
-                    buy_ix.data[22],
-                    buy_ix.data[23],
-                ]);
-            }
-        }
-
-        // 使用当前时间作为时间戳
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        // 初始默认值
-        let initial_sol_reserves: u64 = 30_000_000_000;
-        let initial_token_reserves: u64 = 1_073_000_000_000_000;
-
-        // 计算实际储备值 - 如果已经购买代币，储备会发生变化
-        // 使用恒定乘积公式 k = sol_reserves * token_reserves
-        let k = (initial_sol_reserves as f64) * (initial_token_reserves as f64);
-
-        // 根据代币数量估算当前储备情况
-        // 当token_amount非常小时，几乎等于初始值
-        // 当token_amount较大时，将明显影响储备
-        let current_token_reserves = if token_amount == 0 {
-            initial_token_reserves
-        } else {
-            initial_token_reserves.saturating_sub(token_amount)
-        };
-
-        // 根据恒定乘积公式，计算对应的SOL储备
-        let current_sol_reserves = if current_token_reserves == 0 {
-            initial_sol_reserves // 避免除以零
-        } else {
-            (k / (current_token_reserves as f64)) as u64
-        };
-
-        // 正确计算价格，考虑不同精度
-        // SOL精度为1e9，代币精度为1e6
-        const SOL_DECIMALS: f64 = 1_000_000_000.0; // 10^9
-        const TOKEN_DECIMALS: f64 = 1_000_000.0; // 10^6
-
-        // 计算实际SOL和代币的比例（考虑精度）
-        let price_in_sol = (current_sol_reserves as f64 / SOL_DECIMALS)
-            / (current_token_reserves as f64 / TOKEN_DECIMALS);
-
-        let my_max_sol_cost: u64 = 1200_000_000; // lamports 0.1 sol
-        // // 1. 把 lamports 转成 SOL
-        let max_sol = my_max_sol_cost as f64 / 1_000_000_000.0;
-        let precision_factor = 1_000_000.0;
-
-        let my_buy_token_amount: u64 = (max_sol as f64 / price_in_sol) as u64;
-        // 减少15%的购买数量，以避免滑点错误
-        let reduced_amount = (my_buy_token_amount as f64 * 0.92) as u64;
-        let my_token_amount = (reduced_amount as f64 * precision_factor).floor() as u64;
-
-        // 按照要求格式输出
-        // writeln!(f, "timestamp: {}", timestamp)?;
         writeln!(f, "signature: {}", self.signature)?;
-        // writeln!(f, "mint: {}", mint)?;
-        // writeln!(f, "bonding_curve: {}", bonding_curve)?;
-        // writeln!(f, "associated_bonding_curve: {}", associated_boding_curve)?;
-        // writeln!(f, "user: {}", user_address)?;
-        // writeln!(f, "token_amount: {}", token_amount)?;
-        writeln!(f, "max_sol_cost: {}", max_sol_cost)?;
-        // writeln!(f, "sol_reserves: {}", current_sol_reserves)?;
-        // writeln!(f, "token_reserves: {}", current_token_reserves)?;
-        // writeln!(f, "price: {:.12}", price_in_sol)?;
-        writeln!(f, "name: {}", name)?;
-        writeln!(f, "symbol: {}", symbol)?;
-        // writeln!(f, "uri: {}", uri)?;
-        // writeln!(f, "creator: {}", creator)?;
+        writeln!(f, "max_sol_cost: {}", self.max_sol_cost)?;
+        writeln!(f, "name: {}", self.name)?;
+        writeln!(f, "symbol: {}", self.symbol)?;
 
         Ok(())
     }
 }
 
+// 任意交易中的一笔 PUMP Buy，用于跟单
+#[derive(Debug, Clone)]
+pub struct PumpBuy {
+    pub signature: String,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub associated_bonding_curve: Pubkey,
+    pub amount: u64,
+    pub max_sol_cost: u64,
+}
+
+// 任意交易中的一笔 PUMP Sell，用于识别开发者砸盘
+#[derive(Debug, Clone)]
+pub struct PumpSell {
+    pub signature: String,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
 // PUMP解析器
 #[allow(dead_code)]
 pub struct PumpParser;
 
 impl PumpParser {
     // 解析交易，提取PUMP指令
+    // 返回 Ok(None) 表示不是 Create + Buy 交易，Err 表示指令数据格式错误
     #[allow(dead_code)]
-    pub fn parse_transaction(transaction: &VersionedTransaction) -> Option<PumpTransaction> {
-        // 获取PUMP程序的Pubkey
-        let pump_program_id = Pubkey::from_str(PUMP_PROGRAM_ID).ok()?;
-
+    pub fn parse_transaction(
+        transaction: &VersionedTransaction,
+    ) -> Result<Option<PumpTransaction>, PumpParseError> {
         // 提取PUMP相关指令
-        let pump_instructions = match &transaction.message {
-            VersionedMessage::Legacy(message) => {
-                Self::extract_pump_instructions_from_legacy(message, &pump_program_id)
-            }
-            VersionedMessage::V0(message) => {
-                Self::extract_pump_instructions_from_v0(message, &pump_program_id)
-            }
-        };
+        let pump_instructions = Self::extract_instructions(transaction);
 
         // 如果没有找到PUMP指令，返回None
         if pump_instructions.is_empty() {
-            return None;
+            return Ok(None);
         }
 
         // 获取交易签名
@@ -550,263 +307,218 @@ impl PumpParser {
         }
 
         // 确保有Create和Buy指令（根据需求调整）
-        let create_ix = create_instruction?;
-        let buy_ix = buy_instruction?;
+        let (Some(create_ix), Some(buy_ix)) = (create_instruction, buy_instruction) else {
+            return Ok(None);
+        };
 
         // 从Create指令中提取元数据信息
-        let mut name = String::new();
-        let mut symbol = String::new();
-        let mut uri = String::new();
-        let mut creator = String::new();
-        let mut mint = String::new();
-        let mut bonding_curve = String::new();
-        let mut associated_bonding_curve = String::new();
-        let mut user_address = String::new();
-
-        // 提取Mint、BondingCurve和User地址
-        if create_ix.accounts.len() >= 3 {
-            mint = create_ix.accounts[0].clone();
-            bonding_curve = create_ix.accounts[2].clone();
-            associated_bonding_curve = create_ix.accounts[3].clone();
-
-            if create_ix.accounts.len() >= 8 {
-                user_address = create_ix.accounts[7].clone();
-            }
-        }
+        let create_args = create_ix.create_args()?;
 
-        // 提取元数据
-        if create_ix.data.len() > 8 {
-            let mut offset = 8;
-
-            // 读取name
-            if offset + 4 <= create_ix.data.len() {
-                let name_len = u32::from_le_bytes([
-                    create_ix.data[offset],
-                    create_ix.data[offset + 1],
-                    create_ix.data[offset + 2],
-                    create_ix.data[offset + 3],
-                ]) as usize;
-                offset += 4;
-
-                if offset + name_len <= create_ix.data.len() {
-                    name = String::from_utf8_lossy(&create_ix.data[offset..offset + name_len])
-                        .to_string();
-                    offset += name_len;
-
-                    // 读取symbol
-                    if offset + 4 <= create_ix.data.len() {
-                        let symbol_len = u32::from_le_bytes([
-                            create_ix.data[offset],
-                            create_ix.data[offset + 1],
-                            create_ix.data[offset + 2],
-                            create_ix.data[offset + 3],
-                        ]) as usize;
-                        offset += 4;
-
-                        if offset + symbol_len <= create_ix.data.len() {
-                            symbol = String::from_utf8_lossy(
-                                &create_ix.data[offset..offset + symbol_len],
-                            )
-                            .to_string();
-                            offset += symbol_len;
-
-                            // 读取URI
-                            if offset + 4 <= create_ix.data.len() {
-                                let uri_len = u32::from_le_bytes([
-                                    create_ix.data[offset],
-                                    create_ix.data[offset + 1],
-                                    create_ix.data[offset + 2],
-                                    create_ix.data[offset + 3],
-                                ]) as usize;
-                                offset += 4;
-
-                                if offset + uri_len <= create_ix.data.len() {
-                                    uri = String::from_utf8_lossy(
-                                        &create_ix.data[offset..offset + uri_len],
-                                    )
-                                    .to_string();
-                                    offset += uri_len;
-
-                                    // 读取creator
-                                    if offset + 32 <= create_ix.data.len() {
-                                        creator =
-                                            bs58::encode(&create_ix.data[offset..offset + 32])
-                                                .into_string();
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        // 提取Mint、BondingCurve地址
+        let mint = create_ix.account(0)?.to_string();
+        let bonding_curve = create_ix.account(2)?.to_string();
+        let associated_bonding_curve = create_ix.account(3)?.to_string();
 
         // 从Buy指令提取buy_amount和max_sol_cost
-        let mut buy_amount = 0u64;
-        let mut max_sol_cost = 0u64;
-
-        if buy_ix.data.len() >= 16 {
-            buy_amount = u64::from_le_bytes([
-                buy_ix.data[8],
-                buy_ix.data[9],
-                buy_ix.data[10],
-                buy_ix.data[11],
-                buy_ix.data[12],
-                buy_ix.data[13],
-                buy_ix.data[14],
-                buy_ix.data[15],
-            ]);
-
-            if buy_ix.data.len() >= 24 {
-                max_sol_cost = u64::from_le_bytes([
-                    buy_ix.data[16],
-                    buy_ix.data[17],
-                    buy_ix.data[18],
-                    buy_ix.data[19],
-                    buy_ix.data[20],
-                    buy_ix.data[21],
-                    buy_ix.data[22],
-                    buy_ix.data[23],
-                ]);
-            }
-        } else {
-            return None; // Buy指令数据不足
-        }
-
-        // 计算价格
-        const SOL_DECIMALS: f64 = 1_000_000_000.0; // 10^9
-        const TOKEN_DECIMALS: f64 = 1_000_000.0; // 10^6
-        let initial_sol_reserves: u64 = 30_000_000_000; // 30 SOL in lamports
-        let initial_token_reserves: u64 = 1_073_000_000_000_000; // 1.073B tokens
+        let buy_args = buy_ix.buy_args()?;
+        let buy_amount = buy_args.amount;
+        let max_sol_cost = buy_args.max_sol_cost;
 
-        let k = (initial_sol_reserves as f64) * (initial_token_reserves as f64);
-        let current_token_reserves = initial_token_reserves.saturating_sub(buy_amount);
-        let current_sol_reserves = if current_token_reserves == 0 {
-            initial_sol_reserves
-        } else {
-            (k / (current_token_reserves as f64)) as u64
+        // 发币交易中所有买入（开发者和捆绑钱包）之后的曲线
+        let curve = pump_instructions
+            .iter()
+            .filter(|ix| ix.instruction_type == PumpInstructionType::Buy)
+            .filter(|ix| ix.account(2).is_ok_and(|account| account == mint))
+            .filter_map(|ix| ix.buy_args().ok())
+            .fold(CurveState::initial(), |curve, args| curve.after_buy(args.amount));
+
+        // 构造PumpTransaction，价格和买入数量由 set_curve 计算
+        let mut pump_tx = PumpTransaction {
+            signature,
+            mint,
+            bonding_curve,
+            associated_bonding_curve,
+            creator: create_args.creator.to_string(),
+            name: create_args.name,
+            symbol: create_args.symbol,
+            uri: create_args.uri,
+            buy_amount,
+            max_sol_cost,
+            instructions: pump_instructions,
+            ..Default::default()
         };
+        pump_tx.set_curve(curve);
 
-        let price = if current_token_reserves == 0 {
-            0.0
-        } else {
-            (current_sol_reserves as f64 / SOL_DECIMALS)
-                / (current_token_reserves as f64 / TOKEN_DECIMALS)
+        Ok(Some(pump_tx))
+    }
+
+    // 提取交易中的全部 PUMP 指令（含包装程序内的调用）
+    pub fn extract_instructions(transaction: &VersionedTransaction) -> Vec<PumpInstruction> {
+        let Some((instructions, account_keys)) =
+            Self::message_parts(&transaction.message, Self::is_target_program)
+        else {
+            return Vec::new();
         };
+        Self::extract_pump_instructions(instructions, &account_keys, &PUMP_PROGRAM_PUBKEY)
+    }
+
+    // 解析交易中的全部 Buy 指令，不要求同一笔交易里有 Create
+    pub fn parse_buys(transaction: &VersionedTransaction) -> Result<Vec<PumpBuy>, PumpParseError> {
+        let signature = transaction
+            .signatures
+            .first()
+            .map(|signature| signature.to_string())
+            .unwrap_or_default();
 
-        unsafe {
-            let max_sol = *MAX_SOL;
-            let precision_factor = 1_000_000.0;
-
-            let my_buy_token_amount: u64 = (max_sol as f64 / price) as u64;
-            // 减少15%的购买数量，以避免滑点错误
-            let reduced_amount = (my_buy_token_amount as f64 * 0.94) as u64;
-            let my_token_amount = (reduced_amount as f64 * precision_factor).floor() as u64;
-
-            // 构造PumpTransaction
-            Some(PumpTransaction {
-                signature,
-                mint,
-                bonding_curve,
-                associated_bonding_curve,
-                creator,
-                price,
-                buy_amount,
-                max_sol_cost,
-                my_token_amount,
-                instructions: pump_instructions,
+        Self::extract_instructions(transaction)
+            .iter()
+            .filter(|ix| ix.instruction_type == PumpInstructionType::Buy)
+            .map(|ix| {
+                let args = ix.buy_args()?;
+                Ok(PumpBuy {
+                    signature: signature.clone(),
+                    user: ix.pubkey(6)?,
+                    mint: ix.pubkey(2)?,
+                    bonding_curve: ix.pubkey(3)?,
+                    associated_bonding_curve: ix.pubkey(4)?,
+                    amount: args.amount,
+                    max_sol_cost: args.max_sol_cost,
+                })
             })
-        }
+            .collect()
     }
 
-    // 从Legacy消息中提取PUMP指令
-    fn extract_pump_instructions_from_legacy(
-        message: &LegacyMessage,
-        pump_program_id: &Pubkey,
-    ) -> Vec<PumpInstruction> {
-        let account_keys = &message.account_keys;
-
-        message
-            .instructions
+    // 解析交易中的全部 Sell 指令
+    pub fn parse_sells(
+        transaction: &VersionedTransaction,
+    ) -> Result<Vec<PumpSell>, PumpParseError> {
+        let signature = transaction
+            .signatures
+            .first()
+            .map(|signature| signature.to_string())
+            .unwrap_or_default();
+
+        Self::extract_instructions(transaction)
             .iter()
-            .filter_map(|ix| {
-                let program_idx = ix.program_id_index as usize;
-                if program_idx < account_keys.len() && account_keys[program_idx] == *pump_program_id
-                {
-                    Some(Self::compile_instruction_to_pump_instruction(
-                        ix,
-                        account_keys,
-                    ))
-                } else {
-                    None
-                }
+            .filter(|ix| ix.instruction_type == PumpInstructionType::Sell)
+            .map(|ix| {
+                let args = ix.sell_args()?;
+                Ok(PumpSell {
+                    signature: signature.clone(),
+                    user: ix.pubkey(6)?,
+                    mint: ix.pubkey(2)?,
+                    amount: args.amount,
+                })
             })
             .collect()
     }
 
-    // 从V0消息中提取PUMP指令
-    fn extract_pump_instructions_from_v0(
-        message: &V0Message,
-        pump_program_id: &Pubkey,
-    ) -> Vec<PumpInstruction> {
+    // 是否为需要解析的程序: PUMP 本身或已登记的包装程序
+    pub fn is_target_program(program_id: &Pubkey) -> bool {
+        *program_id == PUMP_PROGRAM_PUBKEY || wrapper_registry::is_wrapper_program(program_id)
+    }
+
+    // 消息的顶层指令和完整账户列表，其它发射台的解析器也使用
+    // V0 消息没有目标程序的指令或查找表未缓存时返回 None
+    pub(crate) fn message_parts(
+        message: &VersionedMessage,
+        is_target: fn(&Pubkey) -> bool,
+    ) -> Option<(&[CompiledInstruction], Cow<'_, [Pubkey]>)> {
+        let message = match message {
+            VersionedMessage::Legacy(message) => {
+                return Some((&message.instructions, Cow::Borrowed(&message.account_keys)));
+            }
+            VersionedMessage::V0(message) => message,
+        };
+
         // 获取静态账户
         let static_keys = &message.account_keys;
 
-        // 如果有地址查找表，简化处理（只处理静态账户）
-        if !message.address_table_lookups.is_empty() {
-            return Vec::new();
+        // 程序ID只能来自静态账户，先判断是否存在目标指令，避免无谓地解析查找表
+        let has_target_instruction = message.instructions.iter().any(|ix| {
+            static_keys
+                .get(ix.program_id_index as usize)
+                .is_some_and(is_target)
+        });
+        if !has_target_instruction {
+            return None;
         }
 
-        message
-            .instructions
+        // 账户顺序: 静态账户 + 查找表可写账户 + 查找表只读账户
+        let account_keys: Cow<[Pubkey]> = if message.address_table_lookups.is_empty() {
+            Cow::Borrowed(static_keys)
+        } else {
+            let loaded = alt_cache::resolve_lookups(&message.address_table_lookups)?;
+
+            let mut keys =
+                Vec::with_capacity(static_keys.len() + loaded.writable.len() + loaded.readonly.len());
+            keys.extend_from_slice(static_keys);
+            keys.extend(loaded.writable);
+            keys.extend(loaded.readonly);
+            Cow::Owned(keys)
+        };
+
+        Some((&message.instructions, account_keys))
+    }
+
+    // 提取顶层PUMP指令，以及经由已知包装程序 CPI 调用的PUMP指令
+    fn extract_pump_instructions(
+        instructions: &[CompiledInstruction],
+        account_keys: &[Pubkey],
+        pump_program_id: &Pubkey,
+    ) -> Vec<PumpInstruction> {
+        instructions
             .iter()
             .filter_map(|ix| {
-                if ix.program_id_index as usize >= static_keys.len() {
-                    return None;
-                }
-
-                let program_id = &static_keys[ix.program_id_index as usize];
+                let program_id = account_keys.get(ix.program_id_index as usize)?;
 
                 if program_id == pump_program_id {
-                    Some(Self::compile_instruction_to_pump_instruction(
-                        ix,
-                        static_keys,
-                    ))
-                } else {
-                    None
+                    return Some(Self::build_pump_instruction(
+                        &ix.data,
+                        &ix.accounts,
+                        account_keys,
+                    ));
                 }
+
+                let (data, accounts) =
+                    wrapper_registry::lift_instruction(program_id, &ix.data, &ix.accounts)?;
+                let pump_ix = Self::build_pump_instruction(&data, accounts, account_keys);
+
+                // 包装程序的其他指令不关心
+                (pump_ix.instruction_type != PumpInstructionType::Unknown).then_some(pump_ix)
             })
             .collect()
     }
 
-    // 将编译后的指令转换为PUMP指令
-    fn compile_instruction_to_pump_instruction(
-        ix: &CompiledInstruction,
+    // 将指令数据和账户索引转换为PUMP指令
+    fn build_pump_instruction(
+        data: &[u8],
+        account_indexes: &[u8],
         account_keys: &[Pubkey],
     ) -> PumpInstruction {
         // 解析指令类型
-        let instruction_type = if !ix.data.is_empty() && ix.data.len() >= 8 {
+        let instruction_type = match data.get(0..8).and_then(|d| <[u8; 8]>::try_from(d).ok()) {
             // 根据discriminator识别指令类型，只处理Buy和Create类型
-            let discriminator = &ix.data[0..8];
-            match discriminator {
+            Some(discriminator) => match discriminator {
                 // Buy指令
-                [102, 6, 61, 18, 1, 218, 235, 234] => PumpInstructionType::Buy,
+                PUMP_BUY_DISCRIMINATOR => PumpInstructionType::Buy,
                 [242, 35, 198, 137, 82, 225, 242, 182] => PumpInstructionType::Buy,
 
+                // Sell指令
+                PUMP_SELL_DISCRIMINATOR => PumpInstructionType::Sell,
+
                 // Create指令
-                [24, 30, 200, 40, 5, 28, 7, 119] => PumpInstructionType::Create,
+                PUMP_CREATE_DISCRIMINATOR => PumpInstructionType::Create,
                 [54, 49, 138, 255, 162, 99, 87, 199] => PumpInstructionType::Create,
 
                 // 其他指令都归类为Unknown
                 _ => PumpInstructionType::Unknown,
-            }
-        } else {
-            PumpInstructionType::Unknown
+            },
+            None => PumpInstructionType::Unknown,
         };
 
         // 获取账户地址
-        let accounts = ix
-            .accounts
+        let accounts = account_indexes
             .iter()
             .filter_map(|account_idx| {
                 account_keys
@@ -818,7 +530,93 @@ impl PumpParser {
         PumpInstruction {
             instruction_type,
             accounts,
-            data: ix.data.clone(),
+            data: data.to_vec(),
         }
     }
 }
+
+// PUMP 发射台
+pub struct PumpVenue;
+
+impl Venue for PumpVenue {
+    fn kind(&self) -> VenueKind {
+        VenueKind::Pump
+    }
+
+    fn is_target_program(&self, program_id: &Pubkey) -> bool {
+        PumpParser::is_target_program(program_id)
+    }
+
+    fn detect_launch(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Option<PumpTransaction>, PumpParseError> {
+        PumpParser::parse_transaction(transaction)
+    }
+
+    fn parse_buys(&self, transaction: &VersionedTransaction) -> Vec<(Pubkey, u64)> {
+        PumpParser::parse_buys(transaction)
+            .map(|buys| buys.into_iter().map(|buy| (buy.mint, buy.amount)).collect())
+            .unwrap_or_default()
+    }
+
+    fn parse_sells(&self, transaction: &VersionedTransaction) -> Vec<VenueTrade> {
+        PumpParser::parse_sells(transaction)
+            .map(|sells| {
+                sells
+                    .into_iter()
+                    .map(|sell| VenueTrade {
+                        signature: sell.signature,
+                        mint: sell.mint,
+                        user: sell.user,
+                        is_buy: false,
+                        token_amount: sell.amount,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // TradeEvent 同时带有最新费率，解出后更新报价用的费率
+    fn decode_trade_events(
+        &self,
+        transaction: &VersionedTransaction,
+        logs: &[String],
+    ) -> Vec<VenueTrade> {
+        let Some(event) = TradeEvent::parse_logs::<TradeEvent>(logs) else {
+            return Vec::new();
+        };
+        update_fee_config(&event);
+        vec![VenueTrade {
+            signature: transaction
+                .signatures
+                .first()
+                .map(|signature| signature.to_string())
+                .unwrap_or_default(),
+            mint: event.mint,
+            user: event.user,
+            is_buy: event.is_buy,
+            token_amount: event.token_amount,
+        }]
+    }
+
+    fn build_buy(&self, buyer: &Pubkey, buy: &LaunchBuy) -> Vec<Instruction> {
+        pump_buy_instructions(buyer, buy)
+    }
+
+    fn build_sell(&self, seller: &Pubkey, sell: &CurveSell) -> Vec<Instruction> {
+        pump_sell_instructions(seller, sell)
+    }
+
+    fn decode_curve(&self, data: &[u8]) -> Option<(CurveState, bool)> {
+        BondingCurveAccount::decode(data).map(|account| (account.curve(), account.complete))
+    }
+
+    fn fee_config(&self) -> FeeConfig {
+        current_fee_config()
+    }
+
+    fn token_decimals(&self) -> f64 {
+        TOKEN_DECIMALS
+    }
+}
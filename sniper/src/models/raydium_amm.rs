@@ -0,0 +1,108 @@
+use anyhow::{Result, anyhow};
+use solana_sdk::pubkey::Pubkey;
+
+// Raydium AMM v4 的 AmmInfo 账户长度，按 mint 查找池子时用于过滤
+pub const AMM_INFO_LEN: u64 = 752;
+// 池子 status 为 6 时允许 swap
+pub const AMM_STATUS_SWAP: u64 = 6;
+
+pub const BASE_MINT_OFFSET: usize = 400;
+pub const QUOTE_MINT_OFFSET: usize = 432;
+
+// AmmInfo 中卖出需要的字段，按固定偏移读取
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmmInfo {
+    pub status: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    // 尚未提取的收益仍在金库里，不算储备
+    pub base_need_take_pnl: u64,
+    pub quote_need_take_pnl: u64,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+impl AmmInfo {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() != AMM_INFO_LEN as usize {
+            return Err(anyhow!("AmmInfo 长度 {} 不正确", data.len()));
+        }
+
+        Ok(Self {
+            status: read_u64(data, 0),
+            swap_fee_numerator: read_u64(data, 176),
+            swap_fee_denominator: read_u64(data, 184),
+            base_need_take_pnl: read_u64(data, 192),
+            quote_need_take_pnl: read_u64(data, 200),
+            base_vault: read_pubkey(data, 336),
+            quote_vault: read_pubkey(data, 368),
+            base_mint: read_pubkey(data, BASE_MINT_OFFSET),
+            quote_mint: read_pubkey(data, QUOTE_MINT_OFFSET),
+        })
+    }
+
+    // 按金库余额计算的储备 (base, quote)
+    pub fn reserves(&self, base_vault_amount: u64, quote_vault_amount: u64) -> (u64, u64) {
+        (
+            base_vault_amount.saturating_sub(self.base_need_take_pnl),
+            quote_vault_amount.saturating_sub(self.quote_need_take_pnl),
+        )
+    }
+
+    // swap_base_in 报价: 手续费从输入中向上取整扣除
+    pub fn swap_quote(&self, amount_in: u64, reserve_in: u64, reserve_out: u64) -> u64 {
+        if amount_in == 0 || self.swap_fee_denominator == 0 {
+            return 0;
+        }
+
+        let fee = (amount_in as u128 * self.swap_fee_numerator as u128)
+            .div_ceil(self.swap_fee_denominator as u128);
+        let amount_in = amount_in as u128 - fee.min(amount_in as u128);
+        let denominator = reserve_in as u128 + amount_in;
+        if denominator == 0 {
+            return 0;
+        }
+
+        (reserve_out as u128 * amount_in / denominator) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_and_quote() {
+        let base_mint = Pubkey::new_unique();
+        let quote_mint = Pubkey::new_unique();
+        let mut data = vec![0u8; AMM_INFO_LEN as usize];
+        data[0..8].copy_from_slice(&AMM_STATUS_SWAP.to_le_bytes());
+        data[176..184].copy_from_slice(&25u64.to_le_bytes());
+        data[184..192].copy_from_slice(&10_000u64.to_le_bytes());
+        data[192..200].copy_from_slice(&1_000u64.to_le_bytes());
+        data[BASE_MINT_OFFSET..BASE_MINT_OFFSET + 32].copy_from_slice(base_mint.as_ref());
+        data[QUOTE_MINT_OFFSET..QUOTE_MINT_OFFSET + 32].copy_from_slice(quote_mint.as_ref());
+
+        let amm = AmmInfo::decode(&data).unwrap();
+        assert_eq!(amm.status, AMM_STATUS_SWAP);
+        assert_eq!(amm.base_mint, base_mint);
+        assert_eq!(amm.quote_mint, quote_mint);
+        assert_eq!(amm.reserves(1_000_000, 500), (999_000, 500));
+
+        // 卖出 1% 的储备，扣 0.25% 手续费后约得 0.99% 的 SOL 储备
+        let out = amm.swap_quote(1_000_000, 100_000_000, 50_000_000_000);
+        assert!(out < 50_000_000_000 / 101);
+        assert!(out > 50_000_000_000 / 101 * 997 / 1000);
+        assert!(AmmInfo::decode(&data[..700]).is_err());
+    }
+}
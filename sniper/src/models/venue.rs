@@ -0,0 +1,126 @@
+use crate::models::PumpTransaction;
+use crate::models::bonding_curve::{CurveState, FeeConfig};
+use crate::models::boop_parser::BoopVenue;
+use crate::models::pump_idl::PumpParseError;
+use crate::models::pump_parser::PumpVenue;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+
+// 发射台标识，随持仓保存，用 venue() 取得对应实现
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum VenueKind {
+    #[default]
+    Pump,
+    Boop,
+}
+
+// 狙击买入需要的参数，由发币交易解析得到
+#[derive(Debug, Clone, Copy)]
+pub struct LaunchBuy {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub associated_bonding_curve: Pubkey,
+    pub creator: Pubkey,
+    pub create_slot: u64,
+    pub token_amount: u64,
+    pub max_sol_cost: u64,
+}
+
+// 在 bonding curve 上卖出，min_sol_output 由调用方按最新储备报价
+#[derive(Debug, Clone, Copy)]
+pub struct CurveSell {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub token_amount: u64,
+    pub min_sol_output: u64,
+}
+
+// 从交易或日志中解出的一笔买卖
+#[derive(Debug, Clone)]
+pub struct VenueTrade {
+    pub signature: String,
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub is_buy: bool,
+    pub token_amount: u64,
+}
+
+// 发射台: 新币在哪里发行、按哪条曲线交易
+// 筛选、风控和退出策略对所有发射台相同，新增发射台只需实现该 trait 并加入 VENUES
+pub trait Venue: Send + Sync {
+    fn kind(&self) -> VenueKind;
+
+    fn is_target_program(&self, program_id: &Pubkey) -> bool;
+
+    // 解析发币交易，Ok(None) 表示不是该发射台的发币交易
+    fn detect_launch(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Option<PumpTransaction>, PumpParseError>;
+
+    // 交易中的买入 (mint, 代币数量)，用于同批次重新报价；没有代币数量的发射台返回空
+    fn parse_buys(&self, _transaction: &VersionedTransaction) -> Vec<(Pubkey, u64)> {
+        Vec::new()
+    }
+
+    // 交易中的卖出，用于在 shred 中提前识别开发者砸盘
+    fn parse_sells(&self, transaction: &VersionedTransaction) -> Vec<VenueTrade>;
+
+    // 价格订阅收到的持仓相关交易中的成交，按日志事件或指令解析
+    fn decode_trade_events(
+        &self,
+        transaction: &VersionedTransaction,
+        logs: &[String],
+    ) -> Vec<VenueTrade>;
+
+    fn build_buy(&self, buyer: &Pubkey, buy: &LaunchBuy) -> Vec<Instruction>;
+
+    fn build_sell(&self, seller: &Pubkey, sell: &CurveSell) -> Vec<Instruction>;
+
+    // 解码链上曲线账户: (曲线, 是否已完成)
+    fn decode_curve(&self, data: &[u8]) -> Option<(CurveState, bool)>;
+
+    fn fee_config(&self) -> FeeConfig;
+
+    // 代币精度 10^decimals，价格和持仓价值都按它换算
+    fn token_decimals(&self) -> f64;
+
+    // 当前价格 (SOL/Token)
+    fn price(&self, curve: &CurveState) -> f64 {
+        curve.price_with_decimals(self.token_decimals())
+    }
+
+    // 卖出报价: 扣手续费和滑点后的最少到手 SOL
+    fn sell_quote(&self, curve: &CurveState, token_amount: u64, slippage_bps: u64) -> u64 {
+        curve.sell_quote_with_slippage(token_amount, &self.fee_config(), slippage_bps)
+    }
+}
+
+pub static VENUES: [&dyn Venue; 2] = [&PumpVenue, &BoopVenue];
+
+pub fn venue(kind: VenueKind) -> &'static dyn Venue {
+    match kind {
+        VenueKind::Pump => &PumpVenue,
+        VenueKind::Boop => &BoopVenue,
+    }
+}
+
+// 任一发射台的程序，用于预加载查找表
+pub fn is_target_program(program_id: &Pubkey) -> bool {
+    VENUES
+        .iter()
+        .any(|venue| venue.is_target_program(program_id))
+}
+
+// 依次按各发射台解析发币交易
+pub fn detect_launch(
+    transaction: &VersionedTransaction,
+) -> Result<Option<PumpTransaction>, PumpParseError> {
+    for venue in VENUES {
+        if let Some(launch) = venue.detect_launch(transaction)? {
+            return Ok(Some(launch));
+        }
+    }
+    Ok(None)
+}
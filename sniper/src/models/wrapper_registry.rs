@@ -0,0 +1,154 @@
+use crate::models::pump_parser::{
+    PUMP_BUY_DISCRIMINATOR, PUMP_CREATE_DISCRIMINATOR, PUMP_SELL_DISCRIMINATOR,
+};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use tokio::time::{self, Duration};
+
+// 已知包装程序（聚合器 / bundler）的指令布局配置
+pub const WRAPPER_PROGRAMS_PATH: &str = "wrapper_programs.json";
+
+// 配置文件中的一条布局，例如:
+// [{"name": "proxy", "program_id": "...", "discriminator": [82, 225, 119, 231, 78, 29, 45, 70],
+//   "data_offset": 8, "account_offset": 0, "instruction_type": "Buy"}]
+#[derive(Debug, Clone, Deserialize)]
+pub struct WrapperLayoutConfig {
+    pub name: String,
+    pub program_id: String,
+    // 包装指令 data 的前缀，用于区分同一程序的不同指令
+    pub discriminator: Vec<u8>,
+    // pump 指令数据在包装指令 data 中的起始位置
+    pub data_offset: usize,
+    // pump 账户在包装指令 accounts 中的起始位置
+    pub account_offset: usize,
+    // 包装指令只带参数不带 pump discriminator 时，用 "Buy" / "Create" / "Sell" 补上
+    #[serde(default)]
+    pub instruction_type: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WrapperLayout {
+    pub name: String,
+    pub discriminator: Vec<u8>,
+    pub data_offset: usize,
+    pub account_offset: usize,
+    pub pump_discriminator: Option<[u8; 8]>,
+}
+
+lazy_static! {
+    // program_id -> 该程序下已知的指令布局
+    pub static ref WRAPPER_REGISTRY: DashMap<Pubkey, Vec<WrapperLayout>> = DashMap::new();
+}
+
+pub fn is_wrapper_program(program_id: &Pubkey) -> bool {
+    WRAPPER_REGISTRY.contains_key(program_id)
+}
+
+// 从包装指令中取出内部 pump 调用的数据和账户索引
+pub fn lift_instruction<'a>(
+    program_id: &Pubkey,
+    data: &[u8],
+    accounts: &'a [u8],
+) -> Option<(Vec<u8>, &'a [u8])> {
+    let layouts = WRAPPER_REGISTRY.get(program_id)?;
+    let layout = layouts
+        .iter()
+        .find(|layout| data.starts_with(&layout.discriminator))?;
+
+    let inner_data = data.get(layout.data_offset..)?;
+    let inner_accounts = accounts.get(layout.account_offset..)?;
+
+    let lifted = match layout.pump_discriminator {
+        Some(discriminator) => {
+            let mut lifted = Vec::with_capacity(8 + inner_data.len());
+            lifted.extend_from_slice(&discriminator);
+            lifted.extend_from_slice(inner_data);
+            lifted
+        }
+        None => inner_data.to_vec(),
+    };
+
+    Some((lifted, inner_accounts))
+}
+
+pub fn load_wrapper_layouts(configs: Vec<WrapperLayoutConfig>) {
+    WRAPPER_REGISTRY.clear();
+
+    for config in configs {
+        let Ok(program_id) = Pubkey::from_str(&config.program_id) else {
+            println!("[包装程序] 无效的 program_id: {}", config.program_id);
+            continue;
+        };
+
+        let pump_discriminator = match config.instruction_type.as_deref() {
+            None => None,
+            Some("Buy") => Some(PUMP_BUY_DISCRIMINATOR),
+            Some("Create") => Some(PUMP_CREATE_DISCRIMINATOR),
+            Some("Sell") => Some(PUMP_SELL_DISCRIMINATOR),
+            Some(other) => {
+                println!("[包装程序] {} 未知的指令类型: {}", config.name, other);
+                continue;
+            }
+        };
+
+        WRAPPER_REGISTRY
+            .entry(program_id)
+            .or_default()
+            .push(WrapperLayout {
+                name: config.name,
+                discriminator: config.discriminator,
+                data_offset: config.data_offset,
+                account_offset: config.account_offset,
+                pump_discriminator,
+            });
+    }
+}
+
+pub async fn watch_wrapper_programs(path: &'static str) {
+    loop {
+        if let Ok(content) = tokio::fs::read_to_string(path).await {
+            match serde_json::from_str::<Vec<WrapperLayoutConfig>>(&content) {
+                Ok(configs) => {
+                    load_wrapper_layouts(configs);
+                    println!("[包装程序] 已更新, 当前 {} 个程序", WRAPPER_REGISTRY.len());
+                }
+                Err(e) => println!("[包装程序] 解析 {} 失败: {:?}", path, e),
+            }
+        }
+
+        time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lift_instruction() {
+        let program_id = Pubkey::new_unique();
+        load_wrapper_layouts(vec![WrapperLayoutConfig {
+            name: "test".to_string(),
+            program_id: program_id.to_string(),
+            discriminator: vec![1, 2],
+            data_offset: 2,
+            account_offset: 1,
+            instruction_type: Some("Buy".to_string()),
+        }]);
+
+        let mut data = vec![1, 2];
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.extend_from_slice(&200u64.to_le_bytes());
+        let accounts = [9, 0, 1, 2];
+
+        let (lifted, inner_accounts) = lift_instruction(&program_id, &data, &accounts).unwrap();
+        assert_eq!(&lifted[..8], &PUMP_BUY_DISCRIMINATOR);
+        assert_eq!(&lifted[8..16], &100u64.to_le_bytes());
+        assert_eq!(inner_accounts, &[0, 1, 2]);
+
+        assert!(lift_instruction(&program_id, &[3, 4], &accounts).is_none());
+    }
+}
@@ -3,16 +3,22 @@ use crate::config::{
     WHITELIST_ENABLED, WHITELIST_HOLD_LESS_5_SEC_COUNT, WHITELIST_MID, WHITELIST_MIN_HOLD,
     WHITELIST_PROFIT, WHITELIST_TOP_3_BUY, ZERO_SLOT_BUY_FEE, ZERO_SLOT_SELL_FEE,
 };
+use crate::models::exit_strategy::{
+    ExitStrategyConfig, exit_strategy_config, update_exit_strategies,
+};
 use crate::monitor::{BLACKLIST_PATH, add_to_blacklist};
+use crate::services::entry_filter::{FilterRule, entry_filters, update_entry_filters};
+use crate::models::bonding_curve::LAMPORTS_PER_SOL;
+use crate::services::{risk, sol_balance};
 use crate::services::transaction_processor::BLACKLIST;
 use serde_json::to_string;
 use sniper_protos::shared::config_service_server::{ConfigService, ConfigServiceServer};
 use sniper_protos::shared::{
-    BlackListResponse, BlacklistRequest, CommonResponse, Config, EmptyRequest, WhitelistConfig,
+    BlackListResponse, BlacklistRequest, CommonResponse, Config, EmptyRequest, EntryFilters,
+    ExitStrategies, SniperStatus, WhitelistConfig,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::fs;
 use tonic::{Request, Response, Status};
@@ -42,15 +48,23 @@ impl ConfigService for MyService {
     ) -> Result<Response<CommonResponse>, Status> {
         let config = request.into_inner();
 
-        let mut map = read_env_file(ENV_PATH).await?;
-        map.insert("BUY_ENABLED".to_string(), config.buy_enabled.to_string());
-        map.insert("MAX_SOL".to_string(), config.max_sol.to_string());
-        map.insert("WHITELIST_ENABLED".to_string(), config.whitelist_enabled.to_string());
-        map.insert("JITO_FEE".to_string(), config.jito_fee.to_string());
-        map.insert("ZERO_SLOT_BUY_FEE".to_string(), config.zero_slot_buy_fee.to_string());
-        map.insert("ZERO_SLOT_SELL_FEE".to_string(), config.zero_slot_sell_fee.to_string());
-
-        write_env_file(ENV_PATH, &map).await?;
+        update_env_vars(
+            ENV_PATH,
+            &[
+                ("BUY_ENABLED", config.buy_enabled.to_string()),
+                ("MAX_SOL", config.max_sol.to_string()),
+                ("WHITELIST_ENABLED", config.whitelist_enabled.to_string()),
+                ("JITO_FEE", config.jito_fee.to_string()),
+                ("ZERO_SLOT_BUY_FEE", config.zero_slot_buy_fee.to_string()),
+                ("ZERO_SLOT_SELL_FEE", config.zero_slot_sell_fee.to_string()),
+            ],
+        )
+        .await?;
+
+        // 手动开启买入视为确认风控停止
+        if config.buy_enabled {
+            risk::resume();
+        }
 
         unsafe {
             *BUY_ENABLED = config.buy_enabled;
@@ -90,17 +104,20 @@ impl ConfigService for MyService {
     ) -> Result<Response<CommonResponse>, Status> {
         let config = request.into_inner();
 
-        let mut map = read_env_file(ENV_PATH).await?;
-        map.insert("WHITELIST_PROFIT".to_string(), config.profit.to_string());
-        map.insert("WHITELIST_AVG".to_string(), config.avg.to_string());
-        map.insert("WHITELIST_COUNT".to_string(), config.count.to_string());
-        map.insert("WHITELIST_MID".to_string(), config.mid.to_string());
-        map.insert("WHITELIST_HOLD_LESS_5_SEC_COUNT".to_string(), config.hold_less_5_sec_count.to_string());
-        map.insert("WHITELIST_MIN_HOLD".to_string(), config.min_hold.to_string());
-        map.insert("WHITELIST_AVG_USER".to_string(), config.avg_user.to_string());
-        map.insert("WHITELIST_TOP_3_BUY".to_string(), config.top_3_buy.to_string());
-
-        write_env_file(ENV_PATH, &map).await?;
+        update_env_vars(
+            ENV_PATH,
+            &[
+                ("WHITELIST_PROFIT", config.profit.to_string()),
+                ("WHITELIST_AVG", config.avg.to_string()),
+                ("WHITELIST_COUNT", config.count.to_string()),
+                ("WHITELIST_MID", config.mid.to_string()),
+                ("WHITELIST_HOLD_LESS_5_SEC_COUNT", config.hold_less_5_sec_count.to_string()),
+                ("WHITELIST_MIN_HOLD", config.min_hold.to_string()),
+                ("WHITELIST_AVG_USER", config.avg_user.to_string()),
+                ("WHITELIST_TOP_3_BUY", config.top_3_buy.to_string()),
+            ],
+        )
+        .await?;
 
         unsafe {
             *WHITELIST_PROFIT = config.profit;
@@ -158,6 +175,77 @@ impl ConfigService for MyService {
             result: "ok".to_string(),
         }))
     }
+
+    async fn get_entry_filters(
+        &self,
+        _request: Request<EmptyRequest>,
+    ) -> Result<Response<EntryFilters>, Status> {
+        let rules = serde_json::to_string(entry_filters().rules())
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(EntryFilters { rules }))
+    }
+
+    async fn update_entry_filters(
+        &self,
+        request: Request<EntryFilters>,
+    ) -> Result<Response<CommonResponse>, Status> {
+        let rules: Vec<FilterRule> = serde_json::from_str(&request.into_inner().rules)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        update_entry_filters(rules)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(Response::new(CommonResponse {
+            result: "ok".to_string(),
+        }))
+    }
+
+    async fn get_exit_strategies(
+        &self,
+        _request: Request<EmptyRequest>,
+    ) -> Result<Response<ExitStrategies>, Status> {
+        let config = serde_json::to_string(&exit_strategy_config())
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(ExitStrategies { config }))
+    }
+
+    async fn update_exit_strategies(
+        &self,
+        request: Request<ExitStrategies>,
+    ) -> Result<Response<CommonResponse>, Status> {
+        let config: ExitStrategyConfig = serde_json::from_str(&request.into_inner().config)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        update_exit_strategies(config)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(Response::new(CommonResponse {
+            result: "ok".to_string(),
+        }))
+    }
+
+    async fn get_status(
+        &self,
+        _request: Request<EmptyRequest>,
+    ) -> Result<Response<SniperStatus>, Status> {
+        let status = risk::status();
+
+        Ok(Response::new(SniperStatus {
+            buy_enabled: unsafe { *BUY_ENABLED },
+            halted_reason: status.halted.unwrap_or_default(),
+            last_rejection: status.last_rejection.unwrap_or_default(),
+            open_positions: status.open_positions as i64,
+            sol_deployed: status.sol_deployed,
+            buys_last_minute: status.buys_last_minute as i64,
+            realized_pnl_today: status.realized_today,
+            wallet_sol: sol_balance::total_lamports() as f64 / LAMPORTS_PER_SOL,
+            low_balance_paused: sol_balance::low_balance_paused(),
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -172,6 +260,19 @@ mod tests {
 
         println!("blacklist set : {:?}", set);
     }
+
+    #[test]
+    fn test_rewrite_env_keeps_other_lines() {
+        let content = "# wallet\nPRIVATE_KEY=\"abc\"\nMAX_SOL=0.01\nJITO_FEE = 0.1\n";
+        let rewritten = rewrite_env(
+            content,
+            &[("MAX_SOL", "0.02".to_string()), ("BUY_ENABLED", "true".to_string())],
+        );
+        assert_eq!(
+            rewritten,
+            "# wallet\nPRIVATE_KEY=\"abc\"\nMAX_SOL=0.02\nJITO_FEE = 0.1\nBUY_ENABLED=true\n"
+        );
+    }
 }
 
 async fn read_blacklist(path: &str) -> Result<HashSet<String>, std::io::Error> {
@@ -202,58 +303,60 @@ async fn write_blacklist(
     Ok(())
 }
 
-/// 读取 .env 文件并解析为键值对 HashMap
-async fn read_env_file(path: &str) -> Result<HashMap<String, String>, std::io::Error> {
-    let content = fs::read_to_string(path).await?;
-    let mut env_map = HashMap::new();
-
-    for line in content.lines() {
-        // 忽略注释和空行
-        let line = line.trim();
-        if line.starts_with('#') || line.is_empty() {
-            continue;
-        }
-
-        // 分割键值对（支持 = 或 : 分隔）
-        if let Some((key, value)) = line.split_once('=') {
-            env_map.insert(key.trim().to_string(), value.trim().to_string());
+// 私钥等敏感配置，配置接口不会改写这些键
+const SECRET_ENV_KEYS: &[&str] = &["PRIVATE_KEY", "KEYSTORE_PATH", "KEYPAIR_PATH", "KEYSTORE_PASSPHRASE_FILE"];
+
+/// 只替换指定键所在的行（不存在则追加），其它行包括注释和敏感配置原样保留
+fn rewrite_env(content: &str, updates: &[(&str, String)]) -> String {
+    let mut written = HashSet::new();
+    let mut lines = content
+        .lines()
+        .map(|line| {
+            let key = line
+                .split_once('=')
+                .map(|(key, _)| key.trim())
+                .filter(|_| !line.trim_start().starts_with('#'));
+            match key.and_then(|key| updates.iter().find(|(k, _)| *k == key)) {
+                Some((key, value)) if written.insert(*key) => format!("{}={}", key, value),
+                _ => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for (key, value) in updates {
+        if !written.contains(key) {
+            lines.push(format!("{}={}", key, value));
         }
     }
 
-    Ok(env_map)
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
 }
 
-/// 将键值对写回 .env 文件
-async fn write_env_file(
-    path: &str,
-    env_map: &HashMap<String, String>,
-) -> Result<(), std::io::Error> {
-    let mut content = String::new();
-
-    // 写入所有键值对
-    for (key, value) in env_map {
-        content.push_str(&format!("{}={}\n", key, value));
+/// 修改 .env 文件中的配置项，先写临时文件再替换，中途失败不会损坏原文件
+async fn update_env_vars(path: &str, updates: &[(&str, String)]) -> Result<(), std::io::Error> {
+    if let Some((key, _)) = updates.iter().find(|(key, _)| SECRET_ENV_KEYS.contains(key)) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("不允许通过配置接口修改 {}", key),
+        ));
     }
 
-    fs::write(path, content).await?;
-    Ok(())
-}
-
-/// 修改 .env 文件中指定键的值（不存在则新增）
-async fn update_env_var(env_path: &str, key: &str, value: &str) -> Result<(), std::io::Error> {
-    // 读取现有 .env 内容
-    let mut env_map = if Path::exists(Path::new(env_path)) {
-        read_env_file(env_path).await?
-    } else {
-        HashMap::new() // 文件不存在则创建新的
+    let content = match fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
     };
 
-    // 修改或新增键值对
-    env_map.insert(key.to_string(), value.to_string());
-
-    // 写回文件
-    write_env_file(env_path, &env_map).await?;
-    Ok(())
+    // 临时文件先设置成原文件的权限再写入内容
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, "").await?;
+    if let Ok(metadata) = fs::metadata(path).await {
+        fs::set_permissions(&tmp_path, metadata.permissions()).await?;
+    }
+    fs::write(&tmp_path, rewrite_env(&content, updates)).await?;
+    fs::rename(&tmp_path, path).await
 }
 
 pub async fn start_server_thread() {
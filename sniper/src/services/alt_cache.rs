@@ -0,0 +1,222 @@
+use crate::api::APP_STATE;
+use anyhow::{Result, anyhow};
+use dashmap::{DashMap, DashSet};
+use lazy_static::lazy_static;
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::message::v0::{LoadedAddresses, MessageAddressTableLookup};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::time::{self, Duration};
+
+// 常用地址查找表列表，启动时预热，懒加载命中的新表也会追加进来
+pub const ALT_PATH: &str = "alt.txt";
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+// getMultipleAccounts 单次最多 100 个账户
+const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+lazy_static! {
+    // 地址查找表缓存: table -> addresses
+    pub static ref ALT_CACHE: DashMap<Pubkey, Arc<Vec<Pubkey>>> = DashMap::new();
+    // 正在拉取中的查找表，避免同一张表被并发请求
+    static ref ALT_PENDING: DashSet<Pubkey> = DashSet::new();
+}
+
+// 用缓存解析 V0 消息的查找表地址，任何一张表缺失或索引越界都返回 None
+pub fn resolve_lookups(lookups: &[MessageAddressTableLookup]) -> Option<LoadedAddresses> {
+    let mut loaded = LoadedAddresses::default();
+
+    for lookup in lookups {
+        let table = ALT_CACHE.get(&lookup.account_key)?;
+
+        for idx in &lookup.writable_indexes {
+            loaded.writable.push(*table.get(*idx as usize)?);
+        }
+        for idx in &lookup.readonly_indexes {
+            loaded.readonly.push(*table.get(*idx as usize)?);
+        }
+    }
+
+    Some(loaded)
+}
+
+// 找出需要拉取的查找表：未缓存，或缓存版本比消息引用的索引短（表被 extend 过）
+pub fn missing_tables(lookups: &[MessageAddressTableLookup]) -> Vec<Pubkey> {
+    lookups
+        .iter()
+        .filter(|lookup| match ALT_CACHE.get(&lookup.account_key) {
+            Some(table) => lookup
+                .writable_indexes
+                .iter()
+                .chain(lookup.readonly_indexes.iter())
+                .any(|idx| *idx as usize >= table.len()),
+            None => true,
+        })
+        .map(|lookup| lookup.account_key)
+        .collect()
+}
+
+// 批量拉取查找表并写入缓存，链上已关闭的表会从缓存中移除
+pub async fn fetch_tables(keys: &[Pubkey]) -> Result<()> {
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+    let client = &app_state.client;
+
+    for chunk in keys.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+        let accounts = client
+            .get_multiple_accounts_with_commitment(chunk, CommitmentConfig::processed())
+            .await?
+            .value;
+
+        for (key, account) in chunk.iter().zip(accounts) {
+            let Some(account) = account else {
+                ALT_CACHE.remove(key);
+                continue;
+            };
+
+            match AddressLookupTable::deserialize(&account.data) {
+                Ok(table) => {
+                    ALT_CACHE.insert(*key, Arc::new(table.addresses.to_vec()));
+                }
+                Err(e) => {
+                    println!("[ALT] 解析查找表失败 {}: {:?}", key, e);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// 解析 PUMP 指令前调用：只有静态账户里包含目标程序的交易才需要等待查找表，
+// 因为被调用的程序 ID 不能来自查找表
+pub async fn ensure_lookups_loaded(message: &VersionedMessage, is_target: fn(&Pubkey) -> bool) {
+    let VersionedMessage::V0(message) = message else {
+        return;
+    };
+
+    if message.address_table_lookups.is_empty() || !message.account_keys.iter().any(is_target) {
+        return;
+    }
+
+    let missing: Vec<Pubkey> = missing_tables(&message.address_table_lookups)
+        .into_iter()
+        .filter(|key| ALT_PENDING.insert(*key))
+        .collect();
+
+    if missing.is_empty() {
+        return;
+    }
+
+    let result = fetch_tables(&missing).await;
+
+    for key in &missing {
+        ALT_PENDING.remove(key);
+    }
+
+    match result {
+        Ok(()) => {
+            println!("[ALT] 懒加载 {} 张查找表", missing.len());
+            if let Err(e) = record_tables(ALT_PATH, &missing).await {
+                println!("[ALT] 写入 {} 失败: {:?}", ALT_PATH, e);
+            }
+        }
+        Err(e) => println!("[ALT] 懒加载查找表失败: {:?}", e),
+    }
+}
+
+// 启动时预热常用查找表
+pub async fn preload_alt_tables(path: &str) {
+    let keys = read_alt_file(path).await;
+    if keys.is_empty() {
+        return;
+    }
+
+    match fetch_tables(&keys).await {
+        Ok(()) => println!("[ALT] 预热完成, 当前 {} 张查找表", ALT_CACHE.len()),
+        Err(e) => println!("[ALT] 预热失败: {:?}", e),
+    }
+}
+
+// 定时刷新已缓存的查找表，跟上 extend / close
+pub async fn refresh_alt_tables_task() {
+    loop {
+        time::sleep(REFRESH_INTERVAL).await;
+
+        let keys: Vec<Pubkey> = ALT_CACHE.iter().map(|entry| *entry.key()).collect();
+        if keys.is_empty() {
+            continue;
+        }
+
+        if let Err(e) = fetch_tables(&keys).await {
+            println!("[ALT] 刷新查找表失败: {:?}", e);
+        }
+    }
+}
+
+async fn read_alt_file(path: &str) -> Vec<Pubkey> {
+    let Ok(content) = tokio::fs::read_to_string(path).await else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .filter_map(|line| Pubkey::from_str(line.trim()).ok())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+async fn record_tables(path: &str, keys: &[Pubkey]) -> Result<(), std::io::Error> {
+    let existing: HashSet<Pubkey> = read_alt_file(path).await.into_iter().collect();
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .await?;
+
+    for key in keys.iter().filter(|key| !existing.contains(key)) {
+        file.write_all(format!("{}\n", key).as_bytes()).await?;
+    }
+    file.flush().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_lookups() {
+        let table_key = Pubkey::new_unique();
+        let addresses: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        ALT_CACHE.insert(table_key, Arc::new(addresses.clone()));
+
+        let lookups = vec![MessageAddressTableLookup {
+            account_key: table_key,
+            writable_indexes: vec![2],
+            readonly_indexes: vec![0, 3],
+        }];
+
+        let loaded = resolve_lookups(&lookups).unwrap();
+        assert_eq!(loaded.writable, vec![addresses[2]]);
+        assert_eq!(loaded.readonly, vec![addresses[0], addresses[3]]);
+        assert!(missing_tables(&lookups).is_empty());
+
+        let stale = vec![MessageAddressTableLookup {
+            account_key: table_key,
+            writable_indexes: vec![4],
+            readonly_indexes: vec![],
+        }];
+        assert!(resolve_lookups(&stale).is_none());
+        assert_eq!(missing_tables(&stale), vec![table_key]);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::api::{APP_STATE, get_account_info_fast};
+use crate::config::BUY_SLIPPAGE_BPS;
+use crate::models::exit_strategy::EntrySource;
+use crate::models::bonding_curve::{BondingCurveAccount, LAMPORTS_PER_SOL, current_fee_config};
+use crate::models::venue::{LaunchBuy, VenueKind, venue};
+use crate::models::{PumpBuy, PumpParser};
+use crate::services::position::{self, open_position};
+use crate::services::risk;
+use crate::services::transaction_processor::TOKEN_TABLE;
+use crate::transaction::venue_buy;
+use anyhow::{Result, anyhow};
+use dashmap::{DashMap, DashSet};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use spl_associated_token_account::get_associated_token_address;
+use std::str::FromStr;
+use tokio::time::{self, Duration};
+
+// 跟单钱包列表
+pub const COPY_WALLETS_PATH: &str = "copy_wallets.json";
+
+// 例如: [{"wallet": "...", "multiplier": 0.5, "max_sol": 0.2}]
+#[derive(Debug, Clone, Deserialize)]
+pub struct CopyWalletConfig {
+    pub wallet: String,
+    // 跟单金额 = 对方实际花费 * multiplier
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    // 单笔跟单上限 (SOL)
+    pub max_sol: f64,
+}
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CopyWallet {
+    pub multiplier: f64,
+    pub max_sol: f64,
+}
+
+impl CopyWallet {
+    // 跟单投入 (lamports)
+    pub fn mirror_amount(&self, their_sol: u64) -> u64 {
+        let scaled = their_sol as f64 * self.multiplier;
+        scaled.min(self.max_sol * LAMPORTS_PER_SOL).max(0.0) as u64
+    }
+}
+
+pub static COPY_WALLETS: Lazy<DashMap<Pubkey, CopyWallet>> = Lazy::new(DashMap::new);
+
+// 正在跟单的 mint，避免同一代币被多个钱包同时触发
+static MIRRORING: Lazy<DashSet<Pubkey>> = Lazy::new(DashSet::new);
+
+// 签名者中是否有跟单钱包，用于在解析前快速过滤
+pub fn has_watched_signer(tx: &VersionedTransaction) -> bool {
+    if COPY_WALLETS.is_empty() {
+        return false;
+    }
+
+    let signers = tx.message.header().num_required_signatures as usize;
+    tx.message
+        .static_account_keys()
+        .iter()
+        .take(signers)
+        .any(|key| COPY_WALLETS.contains_key(key))
+}
+
+// 跟随交易中跟单钱包的 Buy
+pub async fn mirror_buys(tx: VersionedTransaction, slot: u64) {
+    let buys = match PumpParser::parse_buys(&tx) {
+        Ok(buys) => buys,
+        Err(e) => {
+            println!("[跟单] 解析失败 {:?}: {}", tx.signatures.first(), e);
+            return;
+        }
+    };
+
+    for buy in buys {
+        let Some(wallet) = COPY_WALLETS.get(&buy.user).map(|wallet| *wallet) else {
+            continue;
+        };
+
+        if TOKEN_TABLE.contains_key(&buy.mint) || !MIRRORING.insert(buy.mint) {
+            continue;
+        }
+
+        if let Err(e) = mirror_buy(&buy, wallet, slot).await {
+            println!("[跟单] {} 失败: {:?}", buy.mint, e);
+        }
+
+        MIRRORING.remove(&buy.mint);
+    }
+}
+
+async fn mirror_buy(buy: &PumpBuy, wallet: CopyWallet, slot: u64) -> Result<()> {
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+    let client = &app_state.client;
+
+    // 任意阶段的代币都可能被跟单，创建者和储备从链上读取
+    let account = client
+        .get_account_with_commitment(&buy.bonding_curve, CommitmentConfig::processed())
+        .await?
+        .value
+        .ok_or_else(|| anyhow!("bonding curve 不存在"))?;
+    let bonding_curve = BondingCurveAccount::decode(&account.data)
+        .ok_or_else(|| anyhow!("bonding curve 解析失败"))?;
+    if bonding_curve.complete {
+        return Err(anyhow!("已迁移，跳过"));
+    }
+
+    // 按对方买入数量在成交前曲线上的成本跟单，max_sol_cost 只是对方的滑点上限
+    let fees = current_fee_config();
+    let their_sol = bonding_curve
+        .curve()
+        .buy_cost(buy.amount, &fees)
+        .min(buy.max_sol_cost);
+    let sol_amount = wallet.mirror_amount(their_sol);
+    println!(
+        "[跟单] {} 买入 {}, 对方 {} lamports, 跟单 {} lamports, [{}]",
+        buy.user, buy.mint, their_sol, sol_amount, buy.signature
+    );
+    if sol_amount == 0 {
+        return Ok(());
+    }
+
+    // 跟单交易排在对方之后，按对方成交后的曲线报价
+    let curve = bonding_curve.curve().after_buy(buy.amount);
+    let price = curve.price();
+    let token_amount = curve.buy_quote_with_slippage(sol_amount, &fees, *BUY_SLIPPAGE_BPS);
+
+    let wallet = risk::try_reserve_buy(
+        buy.mint,
+        bonding_curve.creator,
+        EntrySource::CopyTrade,
+        sol_amount as f64 / LAMPORTS_PER_SOL,
+    )
+    .map_err(|reason| anyhow!("风控: {}", reason))?;
+    if !position::reserve(
+        buy.mint,
+        buy.bonding_curve,
+        bonding_curve.creator,
+        wallet.pubkey(),
+        EntrySource::CopyTrade,
+        VenueKind::Pump,
+    ) {
+        risk::release_buy(&buy.mint);
+        return Err(anyhow!("已有持仓或买入中"));
+    }
+
+    // 持仓已由本次跟单登记，之后任何一步失败都要放弃
+    let result: Result<()> = async {
+        let launch_buy = LaunchBuy {
+            mint: buy.mint,
+            bonding_curve: buy.bonding_curve,
+            associated_bonding_curve: buy.associated_bonding_curve,
+            creator: bonding_curve.creator,
+            create_slot: slot,
+            token_amount,
+            max_sol_cost: sol_amount,
+        };
+        venue_buy(&wallet, venue(VenueKind::Pump), &launch_buy).await?;
+
+        time::sleep(Duration::from_millis(1500)).await;
+        let ata = get_associated_token_address(&wallet.pubkey(), &buy.mint);
+        let balance = get_account_info_fast(client, &buy.mint, &ata)
+            .await
+            .map_err(|e| anyhow!("查询ATA失败: {:?}", e))?
+            .base
+            .amount;
+
+        if balance == 0 {
+            return Err(anyhow!("未成交"));
+        }
+
+        println!(
+            "[🎯跟单成功] {}, 当前余额为: {}, 购买成本价: {}",
+            buy.mint, balance, price
+        );
+        open_position(
+            buy.mint,
+            buy.bonding_curve,
+            bonding_curve.creator,
+            wallet.pubkey(),
+            price,
+            balance,
+            EntrySource::CopyTrade,
+            VenueKind::Pump,
+        );
+        Ok(())
+    }
+    .await;
+
+    if result.is_err() {
+        position::abandon(buy.mint);
+    }
+    result
+}
+
+pub fn load_copy_wallets(configs: Vec<CopyWalletConfig>) {
+    COPY_WALLETS.clear();
+
+    for config in configs {
+        let Ok(wallet) = Pubkey::from_str(&config.wallet) else {
+            println!("[跟单] 无效的钱包地址: {}", config.wallet);
+            continue;
+        };
+
+        COPY_WALLETS.insert(
+            wallet,
+            CopyWallet {
+                multiplier: config.multiplier,
+                max_sol: config.max_sol,
+            },
+        );
+    }
+}
+
+pub async fn watch_copy_wallets(path: &'static str) {
+    loop {
+        if let Ok(content) = tokio::fs::read_to_string(path).await {
+            match serde_json::from_str::<Vec<CopyWalletConfig>>(&content) {
+                Ok(configs) => {
+                    load_copy_wallets(configs);
+                    println!("[跟单] 已更新, 当前 {} 个钱包", COPY_WALLETS.len());
+                }
+                Err(e) => println!("[跟单] 解析 {} 失败: {:?}", path, e),
+            }
+        }
+
+        time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_amount() {
+        let wallet = CopyWallet {
+            multiplier: 0.5,
+            max_sol: 0.2,
+        };
+
+        assert_eq!(wallet.mirror_amount(100_000_000), 50_000_000);
+        // 超过上限按上限跟单
+        assert_eq!(wallet.mirror_amount(2_000_000_000), 200_000_000);
+    }
+}
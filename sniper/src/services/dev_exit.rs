@@ -0,0 +1,127 @@
+use crate::config::ANALYZER_URL;
+use crate::models::venue::VENUES;
+use crate::models::exit_strategy::exit_strategy_for;
+use crate::monitor::add_to_blacklist;
+use crate::services::position;
+use crate::services::transaction_processor::TOKEN_TABLE;
+use analyzer_protos::shared::DevDumpRequest;
+use analyzer_protos::shared::whitelist_service_client::WhitelistServiceClient;
+use dashmap::{DashMap, DashSet};
+use once_cell::sync::Lazy;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 持仓代币 -> 与开发者关联的钱包（开发者本人之外，在发币交易里一起买入的钱包）
+pub static DEV_WALLETS: Lazy<DashMap<Pubkey, HashSet<Pubkey>>> = Lazy::new(DashMap::new);
+
+// 已处理过砸盘的代币，grpc 和 shred 两条路径可能同时命中
+static EXITING: Lazy<DashSet<Pubkey>> = Lazy::new(DashSet::new);
+
+pub fn link_dev_wallets(mint: Pubkey, wallets: impl IntoIterator<Item = Pubkey>) {
+    DEV_WALLETS.entry(mint).or_default().extend(wallets);
+}
+
+// 持仓平仓后清理
+pub fn forget(mint: &Pubkey) {
+    DEV_WALLETS.remove(mint);
+    EXITING.remove(mint);
+}
+
+pub fn is_dev_wallet(mint: &Pubkey, wallet: &Pubkey) -> bool {
+    let is_creator = TOKEN_TABLE
+        .get(mint)
+        .is_some_and(|state| state.token_creator == *wallet);
+
+    is_creator
+        || DEV_WALLETS
+            .get(mint)
+            .is_some_and(|wallets| wallets.contains(wallet))
+}
+
+// shred 路径: 交易执行前就能看到开发者的卖出指令
+pub fn check_shred_sells(tx: &VersionedTransaction) {
+    if TOKEN_TABLE.is_empty() {
+        return;
+    }
+
+    for sell in VENUES.iter().flat_map(|venue| venue.parse_sells(tx)) {
+        on_sell(sell.mint, sell.user, sell.signature, sell.token_amount, "shred");
+    }
+}
+
+// 持仓代币出现开发者卖出时，用紧急小费全部卖出
+pub fn on_sell(mint: Pubkey, seller: Pubkey, signature: String, token_amount: u64, source: &'static str) {
+    if !is_dev_wallet(&mint, &seller) {
+        return;
+    }
+
+    let Some((creator, balance, entry_source)) = TOKEN_TABLE.get(&mint).map(|state| {
+        (
+            state.token_creator,
+            state.balance.unwrap_or(0),
+            state.entry_source,
+        )
+    }) else {
+        return;
+    };
+
+    // 策略关闭了开发者卖出触发时只记录，不清仓
+    if !exit_strategy_for(entry_source).creator.exit_on_dev_sell {
+        println!(
+            "[开发者卖出][{}] {} 卖出 {} 个 {}, {:?} 策略未启用紧急清仓",
+            source, seller, token_amount, mint, entry_source
+        );
+        return;
+    }
+
+    if !EXITING.insert(mint) {
+        return;
+    }
+
+    println!(
+        "[🚨开发者砸盘][{}] {} 卖出 {} 个 {}, 紧急清仓 {} 个, [{}]",
+        source, seller, token_amount, mint, balance, signature
+    );
+
+    // 由持仓的 owner 任务执行卖出，尚未成交时会在成交后立即退出
+    position::request_emergency_exit(mint, format!("开发者砸盘 {}", seller));
+
+    tokio::spawn(async move {
+        if let Err(e) = add_to_blacklist(&creator.to_string()).await {
+            println!("[狗庄黑名单] 写入失败: {:?}", e);
+        }
+        report_dev_dump(creator, mint, seller, signature, token_amount).await;
+    });
+}
+
+// 记录到 analyzer，用于后续筛选开发者
+async fn report_dev_dump(
+    creator: Pubkey,
+    mint: Pubkey,
+    seller: Pubkey,
+    signature: String,
+    token_amount: u64,
+) {
+    let Ok(mut client) = WhitelistServiceClient::connect(ANALYZER_URL.clone()).await else {
+        println!("[开发者砸盘] 连接 analyzer grpc 失败");
+        return;
+    };
+
+    let request = tonic::Request::new(DevDumpRequest {
+        token_creator: creator.to_string(),
+        token_address: mint.to_string(),
+        seller: seller.to_string(),
+        signature,
+        token_amount: token_amount as i64,
+        detected_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64,
+    });
+
+    if let Err(e) = client.report_dev_dump(request).await {
+        println!("[开发者砸盘] 上报 analyzer 失败: {:?}", e);
+    }
+}
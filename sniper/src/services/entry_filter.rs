@@ -0,0 +1,490 @@
+use crate::config::WHITELIST_ENABLED;
+use crate::models::PumpTransaction;
+use crate::services::transaction_processor::{BLACKLIST, WHITELIST};
+use anyhow::{Result, anyhow};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::time;
+
+// 入场过滤规则链，按顺序执行，任意一条拒绝即不买入
+pub const ENTRY_FILTERS_PATH: &str = "entry_filters.json";
+
+// 一个 leader 连续出 4 个 slot
+const LEADER_SLOTS: u64 = 4;
+
+pub struct FilterContext<'a> {
+    pub pump_tx: &'a PumpTransaction,
+    pub slot: u64,
+}
+
+#[tonic::async_trait]
+pub trait EntryFilter: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // 通过返回 Ok(())，拒绝返回原因
+    async fn check(&self, ctx: &FilterContext<'_>) -> Result<(), String>;
+}
+
+// entry_filters.json 中的一条规则，例如:
+// [{"type": "Blacklist"}, {"type": "DevBuyRange", "min": 300000000, "max": 7000000000},
+//  {"type": "NameRegex", "pattern": "(?i)test", "exclude": true}]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum FilterRule {
+    // 黑名单，包含 "all" 时拒绝全部
+    Blacklist,
+    // WHITELIST_ENABLED 打开时只买白名单里的创建者
+    Whitelist,
+    // 开发者买入的 max_sol_cost 范围 (lamports)
+    DevBuyRange { min: u64, max: u64 },
+    // exclude = true 时命中即拒绝，否则必须命中
+    NameRegex {
+        pattern: String,
+        #[serde(default)]
+        exclude: bool,
+    },
+    SymbolRegex {
+        pattern: String,
+        #[serde(default)]
+        exclude: bool,
+    },
+    // 元数据 uri 的域名白名单，支持后缀匹配，例如 "ipfs.io"
+    UriHost { allowed: Vec<String> },
+    // 创建者在 window_secs 内发币超过 max_launches 个则拒绝
+    CreatorHistory { max_launches: usize, window_secs: u64 },
+    // 只在 leader 窗口内的指定位置买入 (slot % 4)
+    SlotTiming { allowed_offsets: Vec<u64> },
+}
+
+impl FilterRule {
+    pub fn build(&self) -> Result<Box<dyn EntryFilter>> {
+        let filter: Box<dyn EntryFilter> = match self.clone() {
+            FilterRule::Blacklist => Box::new(BlacklistFilter),
+            FilterRule::Whitelist => Box::new(WhitelistFilter),
+            FilterRule::DevBuyRange { min, max } => {
+                if min > max {
+                    return Err(anyhow!("DevBuyRange min {} 大于 max {}", min, max));
+                }
+                Box::new(DevBuyRangeFilter { min, max })
+            }
+            FilterRule::NameRegex { pattern, exclude } => Box::new(RegexFilter {
+                name: "NameRegex",
+                regex: Regex::new(&pattern)?,
+                exclude,
+                field: |tx| &tx.name,
+            }),
+            FilterRule::SymbolRegex { pattern, exclude } => Box::new(RegexFilter {
+                name: "SymbolRegex",
+                regex: Regex::new(&pattern)?,
+                exclude,
+                field: |tx| &tx.symbol,
+            }),
+            FilterRule::UriHost { allowed } => Box::new(UriHostFilter { allowed }),
+            FilterRule::CreatorHistory {
+                max_launches,
+                window_secs,
+            } => Box::new(CreatorHistoryFilter {
+                max_launches,
+                window: Duration::from_secs(window_secs),
+            }),
+            FilterRule::SlotTiming { allowed_offsets } => {
+                if allowed_offsets.iter().any(|offset| *offset >= LEADER_SLOTS) {
+                    return Err(anyhow!("SlotTiming offset 必须小于 {}", LEADER_SLOTS));
+                }
+                Box::new(SlotTimingFilter { allowed_offsets })
+            }
+        };
+
+        Ok(filter)
+    }
+}
+
+// 与原先写死在 process_entries 里的条件一致
+pub fn default_rules() -> Vec<FilterRule> {
+    vec![
+        FilterRule::Blacklist,
+        FilterRule::Whitelist,
+        FilterRule::DevBuyRange {
+            min: 300_000_000,
+            max: 7_000_000_000,
+        },
+    ]
+}
+
+pub struct FilterChain {
+    rules: Vec<FilterRule>,
+    filters: Vec<Box<dyn EntryFilter>>,
+    // CreatorHistory 规则中最长的窗口，没有该规则时不记录发币
+    creator_window: Option<Duration>,
+}
+
+impl FilterChain {
+    pub fn new(rules: Vec<FilterRule>) -> Result<Self> {
+        let filters = rules.iter().map(FilterRule::build).collect::<Result<Vec<_>>>()?;
+        let creator_window = rules
+            .iter()
+            .filter_map(|rule| match rule {
+                FilterRule::CreatorHistory { window_secs, .. } => {
+                    Some(Duration::from_secs(*window_secs))
+                }
+                _ => None,
+            })
+            .max();
+        Ok(Self {
+            rules,
+            filters,
+            creator_window,
+        })
+    }
+
+    pub fn rules(&self) -> &[FilterRule] {
+        &self.rules
+    }
+
+    // 返回 true 表示通过全部规则
+    pub async fn check(&self, ctx: &FilterContext<'_>) -> bool {
+        // 在规则执行前记录发币，被前面的规则拒绝的发币也计入创建者历史
+        if let Some(window) = self.creator_window {
+            record_creator_launch(&ctx.pump_tx.creator, window);
+        }
+
+        for filter in &self.filters {
+            if let Err(reason) = filter.check(ctx).await {
+                println!(
+                    "[入场过滤] {} 被 {} 拒绝: {}",
+                    ctx.pump_tx.mint,
+                    filter.name(),
+                    reason
+                );
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+pub static ENTRY_FILTERS: Lazy<RwLock<Arc<FilterChain>>> = Lazy::new(|| {
+    RwLock::new(Arc::new(
+        FilterChain::new(default_rules()).expect("默认过滤规则无效"),
+    ))
+});
+
+pub fn entry_filters() -> Arc<FilterChain> {
+    ENTRY_FILTERS.read().unwrap().clone()
+}
+
+// 校验并替换规则链，同时写回 entry_filters.json
+pub async fn update_entry_filters(rules: Vec<FilterRule>) -> Result<()> {
+    let chain = FilterChain::new(rules)?;
+    let content = serde_json::to_string_pretty(chain.rules())?;
+    tokio::fs::write(ENTRY_FILTERS_PATH, content).await?;
+
+    *ENTRY_FILTERS.write().unwrap() = Arc::new(chain);
+    Ok(())
+}
+
+pub async fn watch_entry_filters(path: &'static str) {
+    loop {
+        if let Ok(content) = tokio::fs::read_to_string(path).await {
+            let chain = serde_json::from_str::<Vec<FilterRule>>(&content)
+                .map_err(anyhow::Error::from)
+                .and_then(FilterChain::new);
+
+            match chain {
+                Ok(chain) => {
+                    if chain.rules() != entry_filters().rules() {
+                        println!("[入场过滤] 已更新: {:?}", chain.rules());
+                        *ENTRY_FILTERS.write().unwrap() = Arc::new(chain);
+                    }
+                }
+                Err(e) => println!("[入场过滤] 解析 {} 失败: {:?}", path, e),
+            }
+        }
+
+        time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+struct BlacklistFilter;
+
+#[tonic::async_trait]
+impl EntryFilter for BlacklistFilter {
+    fn name(&self) -> &'static str {
+        "Blacklist"
+    }
+
+    async fn check(&self, ctx: &FilterContext<'_>) -> Result<(), String> {
+        let blacklist = BLACKLIST.read().await;
+        if blacklist.contains("all") {
+            return Err("黑名单包含 all".to_string());
+        }
+        if blacklist.contains(ctx.pump_tx.creator.as_str()) {
+            return Err(format!("创建者 {} 在黑名单中", ctx.pump_tx.creator));
+        }
+
+        Ok(())
+    }
+}
+
+struct WhitelistFilter;
+
+#[tonic::async_trait]
+impl EntryFilter for WhitelistFilter {
+    fn name(&self) -> &'static str {
+        "Whitelist"
+    }
+
+    async fn check(&self, ctx: &FilterContext<'_>) -> Result<(), String> {
+        if unsafe { !*WHITELIST_ENABLED } {
+            return Ok(());
+        }
+
+        let whitelist = WHITELIST.read().await;
+        if !whitelist.contains(ctx.pump_tx.creator.as_str()) {
+            return Err(format!("创建者 {} 不在白名单中", ctx.pump_tx.creator));
+        }
+
+        Ok(())
+    }
+}
+
+struct DevBuyRangeFilter {
+    min: u64,
+    max: u64,
+}
+
+#[tonic::async_trait]
+impl EntryFilter for DevBuyRangeFilter {
+    fn name(&self) -> &'static str {
+        "DevBuyRange"
+    }
+
+    async fn check(&self, ctx: &FilterContext<'_>) -> Result<(), String> {
+        let max_sol_cost = ctx.pump_tx.max_sol_cost;
+        if !(self.min..=self.max).contains(&max_sol_cost) {
+            return Err(format!(
+                "开发者买入 {} 不在 [{}, {}] 范围内",
+                max_sol_cost, self.min, self.max
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+struct RegexFilter {
+    name: &'static str,
+    regex: Regex,
+    exclude: bool,
+    field: fn(&PumpTransaction) -> &String,
+}
+
+#[tonic::async_trait]
+impl EntryFilter for RegexFilter {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn check(&self, ctx: &FilterContext<'_>) -> Result<(), String> {
+        let value = (self.field)(ctx.pump_tx);
+        let matched = self.regex.is_match(value);
+
+        if matched && self.exclude {
+            return Err(format!("{:?} 命中排除规则 {}", value, self.regex));
+        }
+        if !matched && !self.exclude {
+            return Err(format!("{:?} 未命中规则 {}", value, self.regex));
+        }
+
+        Ok(())
+    }
+}
+
+struct UriHostFilter {
+    allowed: Vec<String>,
+}
+
+#[tonic::async_trait]
+impl EntryFilter for UriHostFilter {
+    fn name(&self) -> &'static str {
+        "UriHost"
+    }
+
+    async fn check(&self, ctx: &FilterContext<'_>) -> Result<(), String> {
+        let uri = &ctx.pump_tx.uri;
+        let host = reqwest::Url::parse(uri)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .ok_or_else(|| format!("无法解析 uri {:?}", uri))?;
+
+        let allowed = self
+            .allowed
+            .iter()
+            .any(|suffix| host == *suffix || host.ends_with(&format!(".{}", suffix)));
+        if !allowed {
+            return Err(format!("uri 域名 {} 不在允许列表中", host));
+        }
+
+        Ok(())
+    }
+}
+
+// 创建者最近的发币时间
+static CREATOR_LAUNCHES: Lazy<DashMap<String, Vec<Instant>>> = Lazy::new(DashMap::new);
+static LAST_CREATOR_SWEEP: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+// 定期清理窗口内没有发币的创建者，避免长时间运行后无限增长
+const CREATOR_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+fn record_creator_launch(creator: &str, window: Duration) {
+    let now = Instant::now();
+    {
+        let mut launches = CREATOR_LAUNCHES.entry(creator.to_string()).or_default();
+        launches.retain(|time| now.duration_since(*time) <= window);
+        launches.push(now);
+    }
+
+    let mut last_sweep = LAST_CREATOR_SWEEP.lock().unwrap();
+    if now.duration_since(*last_sweep) >= CREATOR_SWEEP_INTERVAL {
+        *last_sweep = now;
+        drop(last_sweep);
+        sweep_creator_launches(now, window);
+    }
+}
+
+fn sweep_creator_launches(now: Instant, window: Duration) {
+    CREATOR_LAUNCHES.retain(|_, launches| {
+        launches.retain(|time| now.duration_since(*time) <= window);
+        !launches.is_empty()
+    });
+}
+
+struct CreatorHistoryFilter {
+    max_launches: usize,
+    window: Duration,
+}
+
+#[tonic::async_trait]
+impl EntryFilter for CreatorHistoryFilter {
+    fn name(&self) -> &'static str {
+        "CreatorHistory"
+    }
+
+    // 发币已由 FilterChain 在规则执行前记录，这里只读
+    async fn check(&self, ctx: &FilterContext<'_>) -> Result<(), String> {
+        let now = Instant::now();
+        let launches = CREATOR_LAUNCHES
+            .get(&ctx.pump_tx.creator)
+            .map(|launches| {
+                launches
+                    .iter()
+                    .filter(|time| now.duration_since(**time) <= self.window)
+                    .count()
+            })
+            .unwrap_or(0);
+
+        if launches > self.max_launches {
+            return Err(format!(
+                "创建者 {} 在 {:?} 内发币 {} 次",
+                ctx.pump_tx.creator, self.window, launches
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+struct SlotTimingFilter {
+    allowed_offsets: Vec<u64>,
+}
+
+#[tonic::async_trait]
+impl EntryFilter for SlotTimingFilter {
+    fn name(&self) -> &'static str {
+        "SlotTiming"
+    }
+
+    async fn check(&self, ctx: &FilterContext<'_>) -> Result<(), String> {
+        let offset = ctx.slot % LEADER_SLOTS;
+        if !self.allowed_offsets.contains(&offset) {
+            return Err(format!("slot {} 位于 leader 窗口第 {} 个", ctx.slot, offset));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_filter_chain() {
+        let rules: Vec<FilterRule> = serde_json::from_str(
+            r#"[{"type": "DevBuyRange", "min": 100, "max": 200},
+                {"type": "SymbolRegex", "pattern": "(?i)^scam", "exclude": true},
+                {"type": "UriHost", "allowed": ["ipfs.io"]},
+                {"type": "SlotTiming", "allowed_offsets": [0, 1]}]"#,
+        )
+        .unwrap();
+        let chain = FilterChain::new(rules).unwrap();
+
+        let mut pump_tx = PumpTransaction {
+            symbol: "DOG".to_string(),
+            uri: "https://gateway.ipfs.io/ipfs/xyz".to_string(),
+            max_sol_cost: 150,
+            ..Default::default()
+        };
+        assert!(chain.check(&FilterContext { pump_tx: &pump_tx, slot: 8 }).await);
+        assert!(!chain.check(&FilterContext { pump_tx: &pump_tx, slot: 7 }).await);
+
+        pump_tx.symbol = "SCAMCOIN".to_string();
+        assert!(!chain.check(&FilterContext { pump_tx: &pump_tx, slot: 8 }).await);
+
+        pump_tx.symbol = "DOG".to_string();
+        pump_tx.uri = "https://evil-ipfs.io/x".to_string();
+        assert!(!chain.check(&FilterContext { pump_tx: &pump_tx, slot: 8 }).await);
+
+        assert!(FilterRule::NameRegex { pattern: "(".to_string(), exclude: false }
+            .build()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_creator_history_counts_rejected_launches() {
+        let rules: Vec<FilterRule> = serde_json::from_str(
+            r#"[{"type": "DevBuyRange", "min": 100, "max": 200},
+                {"type": "CreatorHistory", "max_launches": 1, "window_secs": 60}]"#,
+        )
+        .unwrap();
+        let chain = FilterChain::new(rules).unwrap();
+
+        // 第一次被 DevBuyRange 拒绝，仍计入创建者历史
+        let mut pump_tx = PumpTransaction {
+            creator: "history-creator".to_string(),
+            max_sol_cost: 500,
+            ..Default::default()
+        };
+        assert!(!chain.check(&FilterContext { pump_tx: &pump_tx, slot: 0 }).await);
+
+        pump_tx.max_sol_cost = 150;
+        assert!(!chain.check(&FilterContext { pump_tx: &pump_tx, slot: 0 }).await);
+    }
+
+    #[test]
+    fn test_sweep_creator_launches() {
+        let window = Duration::from_secs(60);
+        let start = Instant::now();
+        let now = start + window * 2;
+        CREATOR_LAUNCHES.insert("sweep-old".to_string(), vec![start]);
+        CREATOR_LAUNCHES.insert("sweep-new".to_string(), vec![now]);
+
+        sweep_creator_launches(now, window);
+        assert!(!CREATOR_LAUNCHES.contains_key("sweep-old"));
+        assert!(CREATOR_LAUNCHES.contains_key("sweep-new"));
+    }
+}
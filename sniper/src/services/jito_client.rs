@@ -13,13 +13,24 @@ use crate::services::transaction_processor::{
 
 use crate::api::get_rpc_client;
 use crate::api::{APP_STATE, AppState};
+use crate::models::wrapper_registry::{WRAPPER_PROGRAMS_PATH, watch_wrapper_programs};
 use crate::monitor::run_yellowstone_listener;
+use crate::services::copy_trade::{COPY_WALLETS_PATH, watch_copy_wallets};
+use crate::models::exit_strategy::{EXIT_STRATEGIES_PATH, watch_exit_strategies};
+use crate::services::entry_filter::{ENTRY_FILTERS_PATH, watch_entry_filters};
+use crate::services::metadata_screen::{METADATA_RULES_PATH, watch_metadata_rules};
+use crate::services::alt_cache::{ALT_PATH, preload_alt_tables, refresh_alt_tables_task};
+use crate::services::pump_global::{load_pump_global, watch_pump_global};
+use crate::services::reconciler::{run_reconciler, watch_wallet_accounts};
+use crate::services::sol_balance::poll_wallet_lamports;
+use crate::services::self_check::run_self_check;
+use crate::services::wallet_pool;
 use crate::tx::{keep_alive_loop, start_blockhash_fetcher};
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use crate::config::{JITO_SHRED_URL, NONCE_PUBKEY, PRIVATE_KEY, PUBLIC_KEY, WHITELIST_AVG, WHITELIST_AVG_USER, WHITELIST_COUNT, WHITELIST_HOLD_LESS_5_SEC_COUNT, WHITELIST_MID, WHITELIST_MIN_HOLD, WHITELIST_PROFIT, WHITELIST_TOP_3_BUY};
+use crate::config::{ANALYZER_URL, JITO_SHRED_URL, RECONCILE_SUBSCRIBE, WHITELIST_AVG, WHITELIST_AVG_USER, WHITELIST_COUNT, WHITELIST_HOLD_LESS_5_SEC_COUNT, WHITELIST_MID, WHITELIST_MIN_HOLD, WHITELIST_PROFIT, WHITELIST_TOP_3_BUY};
 use crate::server::start_server_thread;
 use analyzer_protos::shared::WhitelistRequest;
 use analyzer_protos::shared::whitelist_service_client::WhitelistServiceClient;
@@ -67,7 +78,7 @@ async fn fetch_data_from_api(
     top3buy: f64,
 ) -> Result<Vec<Creator>, reqwest::Error> {
     // 创建 URL 并添加查询参数
-    let url = "http://127.0.0.1:8090/query";
+    let url = format!("{}/query", *ANALYZER_URL);
     let client = Client::new();
 
     // 发送 GET 请求，带上查询参数
@@ -124,7 +135,7 @@ async fn export_to_whitelist(creators: Vec<String>) -> Result<(), Box<dyn std::e
 // 定时任务：每10分钟拉取一次数据并写入 whitelist.txt
 async fn start_periodic_task() {
     let Ok(mut analyzer_grpc_client) =
-        WhitelistServiceClient::connect("http://127.0.0.1:8090").await
+        WhitelistServiceClient::connect(ANALYZER_URL.clone()).await
     else {
         error!("连接 analyzer grpc 失败");
         return;
@@ -168,84 +179,89 @@ async fn clean_token_account_task(client: Arc<RpcClient>) {
     loop {
         time::sleep(INTERVAL).await;
 
-        let keypair = Keypair::from_base58_string(PRIVATE_KEY.as_str());
-
-        let config = RpcAccountInfoConfig {
-            encoding: Some(UiAccountEncoding::Base64),
-            commitment: Some(CommitmentConfig {
-                commitment: CommitmentLevel::Finalized,
-            }),
-            data_slice: None,
-            min_context_slot: None,
-        };
-
-        let token_accounts: RpcResult<Vec<RpcKeyedAccount>> = client
-            .send(
-                RpcRequest::GetTokenAccountsByOwner,
-                json!([
-                    keypair.pubkey().to_string(),
-                    RpcTokenAccountsFilter::ProgramId(spl_token::id().to_string()),
-                    config
-                ]),
-            )
-            .await;
-
-        let token_accounts = token_accounts
-            .map_err(|e| {
-                println!("[‼️ERROR] 查询 token 账户失败, {:?}", e);
-                e
-            })
-            .unwrap()
-            .value;
+        for wallet in wallet_pool::wallets() {
+            close_empty_token_accounts(&client, &wallet.keypair).await;
+        }
+    }
+}
+
+// 关闭钱包里余额为 0 的 token 账户，回收租金
+async fn close_empty_token_accounts(client: &RpcClient, keypair: &Keypair) {
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(CommitmentConfig {
+            commitment: CommitmentLevel::Finalized,
+        }),
+        data_slice: None,
+        min_context_slot: None,
+    };
+
+    let token_accounts: RpcResult<Vec<RpcKeyedAccount>> = client
+        .send(
+            RpcRequest::GetTokenAccountsByOwner,
+            json!([
+                keypair.pubkey().to_string(),
+                RpcTokenAccountsFilter::ProgramId(spl_token::id().to_string()),
+                config
+            ]),
+        )
+        .await;
+
+    let token_accounts = match token_accounts {
+        Ok(response) => response.value,
+        Err(e) => {
+            println!("[‼️ERROR] 查询 token 账户失败, {:?}", e);
+            return;
+        }
+    };
+
+    let token_accounts = token_accounts
+        .into_iter()
+        .filter(|x| {
+            // 反序列化账户数据
+            let token_account = Account::unpack(&x.account.data.decode().unwrap()).unwrap();
+            token_account.amount == 0
+        })
+        .collect::<Vec<_>>();
 
-        let token_accounts = token_accounts
+    if token_accounts.is_empty() {
+        println!("没有账户需要关闭, 退出");
+        return;
+    }
+
+    let chunks = token_accounts.chunks(10);
+    for chunk in chunks {
+        let close_ixs = chunk
             .into_iter()
-            .filter(|x| {
-                // 反序列化账户数据
-                let token_account = Account::unpack(&x.account.data.decode().unwrap()).unwrap();
-                token_account.amount == 0
+            .map(|x| {
+                let account_pubkey = Pubkey::from_str(x.pubkey.as_str()).expect("Pubkey error");
+                spl_token::instruction::close_account(
+                    &spl_token::id(),
+                    &account_pubkey,
+                    &keypair.pubkey(),
+                    &keypair.pubkey(),
+                    &[&keypair.pubkey()],
+                )
+                .unwrap()
             })
             .collect::<Vec<_>>();
 
-        if token_accounts.is_empty() {
-            println!("没有账户需要关闭, 退出");
-            continue;
-        }
-
-        let chunks = token_accounts.chunks(10);
-        for chunk in chunks {
-            let close_ixs = chunk
-                .into_iter()
-                .map(|x| {
-                    let account_pubkey = Pubkey::from_str(x.pubkey.as_str()).expect("Pubkey error");
-                    spl_token::instruction::close_account(
-                        &spl_token::id(),
-                        &account_pubkey,
-                        &keypair.pubkey(),
-                        &keypair.pubkey(),
-                        &[&keypair.pubkey()],
-                    )
-                    .unwrap()
-                })
-                .collect::<Vec<_>>();
-
-            let blockhash = client.get_latest_blockhash().await.unwrap();
-            let tx = Transaction::new_signed_with_payer(
-                &close_ixs,
-                Some(&keypair.pubkey()),
-                &[&keypair],
-                blockhash,
-            );
-
-            let result = client.send_and_confirm_transaction(&tx).await;
-            match result {
-                Ok(signature) => {
-                    println!("Token 账户关闭成功，签名: {}", signature);
-                }
-                Err(_) => {
-                    println!("[⚠️WARN] Token 账户关闭失败. 手动检查下. 等待下次重试");
-                    break;
-                }
+        let blockhash = client.get_latest_blockhash().await.unwrap();
+        let tx = Transaction::new_signed_with_payer(
+            &close_ixs,
+            Some(&keypair.pubkey()),
+            &[keypair],
+            blockhash,
+        );
+
+        let result = client.send_and_confirm_transaction(&tx).await;
+        match result {
+            Ok(signature) => {
+                println!("Token 账户关闭成功，签名: {}", signature);
+            }
+            Err(_) => {
+                println!("[⚠️WARN] Token 账户关闭失败. 手动检查下. 等待下次重试");
+                break;
             }
         }
     }
@@ -270,16 +286,49 @@ impl JitoClient {
         let app_state = APP_STATE.get().expect("AppState not initialized");
         let client = &app_state.client;
 
+        // 启动自检，失败时给出具体原因并拒绝启动
+        let report = run_self_check(client).await;
+        if !report.passed() {
+            println!("[自检] 未通过:\n{}", report);
+            return Err(io::Error::other("启动自检未通过"));
+        }
+        println!("[自检] 通过:\n{}", report);
+
         // 定时任务，间隔10分钟执行一次
         tokio::spawn(clean_token_account_task(client.clone()));
         tokio::spawn(start_server_thread());
         tokio::spawn(start_periodic_task());
         tokio::spawn(watch_blacklist_txt("blacklist.txt"));
         tokio::spawn(watch_whitelist_txt("whitelist.txt"));
+        tokio::spawn(watch_wrapper_programs(WRAPPER_PROGRAMS_PATH));
+        tokio::spawn(watch_entry_filters(ENTRY_FILTERS_PATH));
+        tokio::spawn(watch_exit_strategies(EXIT_STRATEGIES_PATH));
+        tokio::spawn(watch_metadata_rules(METADATA_RULES_PATH));
+        tokio::spawn(watch_copy_wallets(COPY_WALLETS_PATH));
+
+        // 拉取 PUMP Global 参数，失败时使用默认值，订阅成功后会自动纠正
+        if let Err(e) = load_pump_global().await {
+            println!("[Global] 启动拉取失败, 使用默认参数: {:?}", e);
+        }
+        tokio::spawn(watch_pump_global());
 
-        let nonce_pubkey2 = Pubkey::from_str(NONCE_PUBKEY.as_str()).unwrap();
-        let nonce_pubkey = Arc::new(nonce_pubkey2);
-        start_blockhash_fetcher(&app_state, nonce_pubkey).await;
+        // 钱包 SOL 余额，买入前检查
+        tokio::spawn(poll_wallet_lamports());
+
+        // 持仓与钱包余额对账
+        tokio::spawn(run_reconciler());
+        if *RECONCILE_SUBSCRIBE {
+            tokio::spawn(watch_wallet_accounts());
+        }
+
+        preload_alt_tables(ALT_PATH).await;
+        tokio::spawn(refresh_alt_tables_task());
+
+        for wallet in wallet_pool::wallets() {
+            if let Err(e) = start_blockhash_fetcher(app_state, wallet).await {
+                println!("[钱包池] 钱包 {} 初始化 nonce 失败: {:?}", wallet.name, e);
+            }
+        }
 
         tokio::spawn(keep_alive_loop());
         tokio::spawn(run_yellowstone_listener());
@@ -370,6 +419,8 @@ impl JitoClient {
 
     // 创建一个同步方法启动客户端
     pub fn start() -> Result<(), io::Error> {
+        wallet_pool::load_wallet_pool();
+
         // 配置tokio运行
         let rt = Runtime::new().map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
 
@@ -0,0 +1,173 @@
+use crate::config::METADATA_TIMEOUT_MS;
+use anyhow::{Result, anyhow};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::time;
+
+// 元数据筛查规则，买入与筛查并行，筛查不通过则立即卖出
+pub const METADATA_RULES_PATH: &str = "metadata_rules.json";
+
+// 例如: {"require_image": true, "required_socials": ["twitter", "telegram"],
+//        "banned_keywords": ["rug", "test"], "allow_unreachable": false}
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MetadataRules {
+    pub require_image: bool,
+    // 至少包含其中一个社交链接
+    pub required_socials: Vec<String>,
+    // name / symbol / description 中出现即拒绝，忽略大小写
+    pub banned_keywords: Vec<String>,
+    // 元数据拉取失败或超时是否放行
+    pub allow_unreachable: bool,
+}
+
+impl Default for MetadataRules {
+    fn default() -> Self {
+        Self {
+            require_image: true,
+            required_socials: vec![],
+            banned_keywords: vec![],
+            allow_unreachable: false,
+        }
+    }
+}
+
+impl MetadataRules {
+    // 通过返回 Ok(())，不通过返回原因
+    pub fn check(&self, metadata: &Value) -> Result<(), String> {
+        let field = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+
+        if self.require_image && field("image").is_none() {
+            return Err("缺少 image".to_string());
+        }
+
+        if !self.required_socials.is_empty()
+            && !self.required_socials.iter().any(|key| field(key).is_some())
+        {
+            return Err(format!("缺少社交链接 {:?}", self.required_socials));
+        }
+
+        let text = ["name", "symbol", "description"]
+            .iter()
+            .filter_map(|key| field(key))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        if let Some(keyword) = self
+            .banned_keywords
+            .iter()
+            .find(|keyword| text.contains(&keyword.to_lowercase()))
+        {
+            return Err(format!("包含关键词 {:?}", keyword));
+        }
+
+        Ok(())
+    }
+}
+
+static METADATA_RULES: Lazy<RwLock<MetadataRules>> =
+    Lazy::new(|| RwLock::new(MetadataRules::default()));
+
+static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .timeout(Duration::from_millis(*METADATA_TIMEOUT_MS))
+        .build()
+        .expect("初始化 HTTP 客户端失败")
+});
+
+// uri -> 元数据，拉取失败的 uri 不缓存，下次重试
+static METADATA_CACHE: Lazy<DashMap<String, Arc<Value>>> = Lazy::new(DashMap::new);
+
+pub fn metadata_rules() -> MetadataRules {
+    METADATA_RULES.read().unwrap().clone()
+}
+
+async fn fetch_metadata(uri: &str) -> Result<Arc<Value>> {
+    if let Some(metadata) = METADATA_CACHE.get(uri) {
+        return Ok(metadata.clone());
+    }
+
+    let response = HTTP_CLIENT.get(uri).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("HTTP {}", response.status()));
+    }
+
+    let metadata = Arc::new(response.json::<Value>().await?);
+    METADATA_CACHE.insert(uri.to_string(), metadata.clone());
+    Ok(metadata)
+}
+
+// 拉取并检查代币元数据
+pub async fn screen_metadata(uri: String) -> Result<(), String> {
+    let rules = metadata_rules();
+
+    match fetch_metadata(&uri).await {
+        Ok(metadata) => rules.check(&metadata),
+        Err(e) if rules.allow_unreachable => {
+            println!("[元数据] 拉取 {} 失败, 放行: {:?}", uri, e);
+            Ok(())
+        }
+        Err(e) => Err(format!("拉取 {} 失败: {}", uri, e)),
+    }
+}
+
+pub async fn watch_metadata_rules(path: &'static str) {
+    loop {
+        if let Ok(content) = tokio::fs::read_to_string(path).await {
+            match serde_json::from_str::<MetadataRules>(&content) {
+                Ok(rules) => {
+                    if rules != metadata_rules() {
+                        println!("[元数据] 规则已更新: {:?}", rules);
+                        *METADATA_RULES.write().unwrap() = rules;
+                    }
+                }
+                Err(e) => println!("[元数据] 解析 {} 失败: {:?}", path, e),
+            }
+        }
+
+        time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_metadata_rules() {
+        let rules = MetadataRules {
+            require_image: true,
+            required_socials: vec!["twitter".to_string(), "telegram".to_string()],
+            banned_keywords: vec!["Rug".to_string()],
+            allow_unreachable: false,
+        };
+
+        let metadata = json!({
+            "name": "Dog",
+            "symbol": "DOG",
+            "image": "https://ipfs.io/ipfs/xyz",
+            "telegram": "https://t.me/dog",
+        });
+        assert!(rules.check(&metadata).is_ok());
+
+        assert!(rules.check(&json!({"name": "Dog", "telegram": "x"})).is_err());
+        assert!(rules.check(&json!({"image": "x", "twitter": " "})).is_err());
+        assert!(
+            rules
+                .check(&json!({"image": "x", "twitter": "x", "description": "no rugpull"}))
+                .is_err()
+        );
+    }
+}
@@ -1,5 +1,17 @@
+pub mod alt_cache;
+pub mod copy_trade;
+pub mod dev_exit;
+pub mod entry_filter;
 pub mod jito_client;
+pub mod metadata_screen;
+pub mod position;
+pub mod pump_global;
+pub mod reconciler;
+pub mod risk;
+pub mod self_check;
+pub mod sol_balance;
 pub mod transaction_processor;
+pub mod wallet_pool;
 // 虽然这些导出在当前bin中未使用，但在lib.rs中被使用，所以需要保留
 #[allow(unused_imports)]
 pub use jito_client::JitoClient;
@@ -0,0 +1,740 @@
+use crate::api::{APP_STATE, get_account_info_fast};
+use crate::config::{EMERGENCY_SELL_SLIPPAGE_BPS, SELL_SLIPPAGE_BPS};
+use crate::models::exit_strategy::{EntrySource, ExitDecision, PositionSnapshot, exit_strategy_for};
+use crate::monitor::{add_to_blacklist, watched_curves_changed};
+use crate::models::bonding_curve::{CurveState, LAMPORTS_PER_SOL};
+use crate::models::venue::{CurveSell, VenueKind, venue};
+use crate::services::wallet_pool::{self, Wallet};
+use crate::services::{dev_exit, risk, sol_balance};
+use crate::services::transaction_processor::{CURVE_INDEX, TOKEN_TABLE, update_token_state};
+use crate::transaction::{
+    find_pump_amm_pool, find_raydium_pool, pump_amm_sell_with_tip, raydium_sell_with_tip,
+    venue_sell_with_tip,
+};
+use crate::tx::TipProfile;
+use anyhow::{Result, anyhow};
+use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
+use once_cell::sync::Lazy;
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address;
+use std::time::Instant;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::{self, Duration};
+
+// 每个持仓由唯一的 owner 任务驱动，其它模块只发送事件，不直接修改卖出状态
+//
+// Pending(已发出买入) -> Open(已成交) -> Exiting(卖出中) -> Closed
+//                                              └-> Failed(重试耗尽，下次事件再试)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PositionPhase {
+    #[default]
+    Pending,
+    Open,
+    Exiting,
+    Closed,
+    Failed,
+}
+
+#[derive(Debug)]
+pub enum PositionEvent {
+    // 买入成交，余额来自链上
+    Filled { price: f64, balance: u64 },
+    // 价格订阅得到的最新储备
+    Price(CurveState),
+    // 时间规则到期，用当前价复查
+    TimeCheck,
+    // 外部触发的全部卖出，例如开发者砸盘
+    // emergency: 因滑点未成交时放宽最少到手重发
+    Exit {
+        reason: String,
+        tip: TipProfile,
+        emergency: bool,
+    },
+    // 买入未成交
+    Abandon,
+    // 对账得到的链上余额
+    Reconcile(u64),
+    // bonding curve 已完成，之后在迁移后的池子卖出
+    Migrated,
+}
+
+#[derive(Debug, Clone)]
+struct ExitOrder {
+    amount: u64,
+    // 卖出后的 sell_stage
+    stage: u8,
+    close: bool,
+    blacklist: bool,
+    tip: TipProfile,
+    // 最少到手 SOL 的滑点容忍度 (基点)
+    slippage_bps: u64,
+    emergency: bool,
+    reason: String,
+}
+
+impl ExitOrder {
+    // 紧急退出没有成交时放宽滑点重发，已经放宽过或不是紧急退出则返回 None
+    fn loosened(&self) -> Option<Self> {
+        let slippage_bps = (*EMERGENCY_SELL_SLIPPAGE_BPS).min(10_000);
+        (self.emergency && self.slippage_bps < slippage_bps).then(|| Self {
+            slippage_bps,
+            ..self.clone()
+        })
+    }
+}
+
+// 单次退出的发送重试次数
+const SELL_RETRIES: u32 = 3;
+// 卖出发送后等待上链再核对余额
+const SETTLE_DELAY: Duration = Duration::from_millis(1500);
+// 迁移后没有价格推送，卖出失败时定时重试（池子可能还没创建）
+const MIGRATED_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+static POSITIONS: Lazy<DashMap<Pubkey, UnboundedSender<PositionEvent>>> = Lazy::new(DashMap::new);
+
+// 发出买入前登记持仓并启动 owner 任务，wallet 为发出买入的钱包
+// 该 mint 已有持仓时返回 false，调用方不能再发出买入
+pub fn reserve(
+    mint: Pubkey,
+    bonding_curve: Pubkey,
+    creator: Pubkey,
+    wallet: Pubkey,
+    source: EntrySource,
+    venue: VenueKind,
+) -> bool {
+    let (tx, rx) = mpsc::unbounded_channel();
+    match POSITIONS.entry(mint) {
+        Entry::Occupied(_) => return false,
+        Entry::Vacant(entry) => {
+            entry.insert(tx);
+        }
+    }
+
+    update_token_state(mint, |state| {
+        state.bonding_curve = Some(bonding_curve);
+        state.token_creator = creator;
+        state.entry_source = source;
+        state.wallet = Some(wallet);
+        state.venue = venue;
+        state.phase = PositionPhase::Pending;
+    });
+    CURVE_INDEX.insert(bonding_curve, mint);
+    watched_curves_changed();
+
+    // 买入前的钱包余额，成交后按余额变化记录实际花费；接管的持仓没有买入
+    let lamports_before = match source {
+        EntrySource::Adopted => None,
+        _ => sol_balance::wallet_lamports(&wallet),
+    };
+    tokio::spawn(run_position(mint, rx, lamports_before));
+    true
+}
+
+// 买入成交后登记持仓，之后由 owner 任务按退出策略卖出
+#[allow(clippy::too_many_arguments)]
+pub fn open_position(
+    mint: Pubkey,
+    bonding_curve: Pubkey,
+    creator: Pubkey,
+    wallet: Pubkey,
+    price: f64,
+    balance: u64,
+    source: EntrySource,
+    venue: VenueKind,
+) {
+    // 买入前已经登记过的持仓这里返回 false，成交照常通知
+    reserve(mint, bonding_curve, creator, wallet, source, venue);
+    notify(mint, PositionEvent::Filled { price, balance });
+}
+
+pub fn notify(mint: Pubkey, event: PositionEvent) -> bool {
+    POSITIONS
+        .get(&mint)
+        .is_some_and(|owner| owner.send(event).is_ok())
+}
+
+pub fn on_price(mint: Pubkey, curve: CurveState) {
+    notify(mint, PositionEvent::Price(curve));
+}
+
+pub fn request_exit(mint: Pubkey, reason: String, tip: TipProfile) -> bool {
+    notify(
+        mint,
+        PositionEvent::Exit {
+            reason,
+            tip,
+            emergency: false,
+        },
+    )
+}
+
+// 紧急清仓: 提高小费，未成交时放宽最少到手重发
+pub fn request_emergency_exit(mint: Pubkey, reason: String) -> bool {
+    notify(
+        mint,
+        PositionEvent::Exit {
+            reason,
+            tip: TipProfile::emergency(),
+            emergency: true,
+        },
+    )
+}
+
+pub fn on_migrated(mint: Pubkey) {
+    notify(mint, PositionEvent::Migrated);
+}
+
+pub fn abandon(mint: Pubkey) {
+    notify(mint, PositionEvent::Abandon);
+}
+
+pub fn phase(mint: &Pubkey) -> Option<PositionPhase> {
+    TOKEN_TABLE.get(mint).map(|state| state.phase)
+}
+
+fn set_phase(mint: &Pubkey, phase: PositionPhase) {
+    if let Some(mut state) = TOKEN_TABLE.get_mut(mint) {
+        state.phase = phase;
+    }
+}
+
+async fn run_position(
+    mint: Pubkey,
+    mut rx: UnboundedReceiver<PositionEvent>,
+    mut lamports_before: Option<u64>,
+) {
+    // 重试耗尽的退出，下一次事件时优先重试
+    let mut failed: Option<ExitOrder> = None;
+    // 成交前收到的退出请求，成交后立即执行
+    let mut deferred: Option<(String, TipProfile, bool)> = None;
+
+    while let Some(event) = rx.recv().await {
+        let order = match event {
+            PositionEvent::Filled { price, balance } => {
+                on_filled(mint, price, balance, lamports_before.take(), deferred.take()).await
+            }
+            PositionEvent::Price(curve) => evaluate(&mint, Some(curve)),
+            PositionEvent::TimeCheck => evaluate(&mint, None),
+            PositionEvent::Exit {
+                reason,
+                tip,
+                emergency,
+            } => {
+                if phase(&mint) == Some(PositionPhase::Pending) {
+                    println!("[持仓] {} 尚未成交, 成交后执行退出: {}", mint, reason);
+                    deferred = Some((reason, tip, emergency));
+                    None
+                } else {
+                    exit_all(&mint, reason, tip, emergency)
+                }
+            }
+            PositionEvent::Reconcile(balance) => {
+                reconcile(&mint, balance);
+                None
+            }
+            PositionEvent::Migrated => on_migrated_event(&mint),
+            PositionEvent::Abandon => {
+                if phase(&mint) == Some(PositionPhase::Pending) {
+                    println!("[持仓] {} 未成交, 放弃", mint);
+                    break;
+                }
+                None
+            }
+        };
+
+        if let Some(order) = order.or_else(|| failed.take()) {
+            failed = execute(&mint, order).await;
+            if failed.is_some() && migrated(&mint) {
+                tokio::spawn(async move {
+                    time::sleep(MIGRATED_RETRY_DELAY).await;
+                    notify(mint, PositionEvent::TimeCheck);
+                });
+            }
+        }
+
+        if phase(&mint) == Some(PositionPhase::Closed) {
+            println!("[持仓] {} 已平仓", mint);
+            break;
+        }
+    }
+
+    POSITIONS.remove(&mint);
+    if let Some((_, state)) = TOKEN_TABLE.remove(&mint)
+        && let Some(bonding_curve) = state.bonding_curve
+    {
+        CURVE_INDEX.remove(&bonding_curve);
+    }
+    dev_exit::forget(&mint);
+    risk::record_close(&mint);
+    watched_curves_changed();
+}
+
+// 成交前收到的退出请求或迁移，成交后立即清仓
+async fn on_filled(
+    mint: Pubkey,
+    price: f64,
+    balance: u64,
+    lamports_before: Option<u64>,
+    deferred: Option<(String, TipProfile, bool)>,
+) -> Option<ExitOrder> {
+    let now = Instant::now();
+    let mut source = EntrySource::default();
+    let mut wallet = Pubkey::default();
+    let mut kind = VenueKind::default();
+    update_token_state(mint, |state| {
+        state.first_buy_price.get_or_insert(price);
+        state.current_price.get_or_insert(price);
+        state.balance = Some(balance);
+        state.last_tx_time.get_or_insert(now);
+        state.last_tx_price.get_or_insert(price);
+        state.first_buy_time.get_or_insert(now);
+        if state.highest_price < price {
+            state.highest_price = price;
+        }
+        state.phase = PositionPhase::Open;
+        source = state.entry_source;
+        wallet = state.wallet.unwrap_or_default();
+        kind = state.venue;
+    });
+    let cost = match wallet_sol_delta(wallet, lamports_before).await {
+        Some(delta) if delta < 0.0 => -delta,
+        _ => sol_value(balance, price, kind),
+    };
+    risk::record_fill(mint, wallet, cost);
+
+    // 没有新成交时价格不会更新，按策略的时间规则定时复查
+    for rule in &exit_strategy_for(source).time_rules {
+        let delay = Duration::from_millis(rule.after_ms);
+        tokio::spawn(async move {
+            time::sleep(delay).await;
+            notify(mint, PositionEvent::TimeCheck);
+        });
+    }
+
+    match deferred {
+        Some((reason, tip, emergency)) => exit_all(&mint, reason, tip, emergency),
+        None if migrated(&mint) => migrated_exit(&mint),
+        None => None,
+    }
+}
+
+fn migrated(mint: &Pubkey) -> bool {
+    TOKEN_TABLE.get(mint).is_some_and(|state| state.migrated)
+}
+
+// 迁移后价格不再更新，退出策略无法继续评估，直接在迁移后的池子清仓
+fn on_migrated_event(mint: &Pubkey) -> Option<ExitOrder> {
+    let kind = {
+        let mut state = TOKEN_TABLE.get_mut(mint)?;
+        if state.migrated {
+            return None;
+        }
+        state.migrated = true;
+        state.venue
+    };
+
+    // 迁移后的卖出只支持 PUMP (PumpSwap / Raydium AMM v4)，其它发射台毕业后停止跟踪，避免无限重试
+    if kind != VenueKind::Pump {
+        println!(
+            "[❌持仓] {} 已从 {:?} 毕业, 不支持毕业后池子的卖出, 停止跟踪, 请手动处理",
+            mint, kind
+        );
+        set_phase(mint, PositionPhase::Closed);
+        return None;
+    }
+
+    if phase(mint) == Some(PositionPhase::Pending) {
+        println!("[持仓] {} 已迁移, 买入尚未成交, 成交后清仓", mint);
+        return None;
+    }
+    migrated_exit(mint)
+}
+
+fn migrated_exit(mint: &Pubkey) -> Option<ExitOrder> {
+    exit_all(mint, "bonding curve 已完成迁移".to_string(), TipProfile::normal(), false)
+}
+
+fn evaluate(mint: &Pubkey, curve: Option<CurveState>) -> Option<ExitOrder> {
+    let mut state = TOKEN_TABLE.get_mut(mint)?;
+    if state.migrated {
+        return None;
+    }
+
+    if let Some(curve) = curve {
+        let price = venue(state.venue).price(&curve);
+        state.curve = Some(curve);
+        state.current_price = Some(price);
+        if price > state.highest_price {
+            state.highest_price = price;
+        }
+    }
+
+    if !matches!(state.phase, PositionPhase::Open | PositionPhase::Failed) {
+        return None;
+    }
+
+    let (Some(first_buy_price), Some(balance), Some(price)) =
+        (state.first_buy_price, state.balance, state.current_price)
+    else {
+        return None;
+    };
+
+    let change = (price - first_buy_price) / first_buy_price;
+    let strategy = exit_strategy_for(state.entry_source);
+    let decision = strategy.evaluate(&PositionSnapshot {
+        first_buy_price,
+        price,
+        highest_price: state.highest_price,
+        sell_stage: state.sell_stage,
+        held: state.first_buy_time.map(|time| time.elapsed()),
+        balance,
+    });
+
+    match decision {
+        ExitDecision::Hold => {
+            println!(
+                "[😌代币地址: {}] 价格变动未超过阈值，首次买入价: {:.12}, 当前价: {:.12}, 变动: {:.2}%",
+                mint,
+                first_buy_price,
+                price,
+                change * 100.0
+            );
+            None
+        }
+        ExitDecision::Sell {
+            amount,
+            stage,
+            close,
+            blacklist,
+            reason,
+        } => Some(ExitOrder {
+            amount,
+            stage,
+            close,
+            blacklist,
+            tip: TipProfile::normal(),
+            slippage_bps: *SELL_SLIPPAGE_BPS,
+            emergency: false,
+            reason: format!(
+                "{}, {}, 变动: {:.2}%",
+                strategy.name,
+                reason,
+                change * 100.0
+            ),
+        }),
+    }
+}
+
+fn exit_all(mint: &Pubkey, reason: String, tip: TipProfile, emergency: bool) -> Option<ExitOrder> {
+    let state = TOKEN_TABLE.get(mint)?;
+    if !matches!(state.phase, PositionPhase::Open | PositionPhase::Failed) {
+        return None;
+    }
+
+    let balance = state.balance.filter(|balance| *balance > 0)?;
+    Some(ExitOrder {
+        amount: balance,
+        stage: state.sell_stage,
+        close: true,
+        blacklist: false,
+        tip,
+        slippage_bps: *SELL_SLIPPAGE_BPS,
+        emergency,
+        reason,
+    })
+}
+
+// 卖出中和未成交的持仓余额还在变化，不参与对账
+fn reconcile(mint: &Pubkey, balance: u64) {
+    let Some(mut state) = TOKEN_TABLE.get_mut(mint) else {
+        return;
+    };
+    if !matches!(state.phase, PositionPhase::Open | PositionPhase::Failed)
+        || state.balance == Some(balance)
+    {
+        return;
+    }
+
+    println!(
+        "[对账] {} 记录余额 {:?}, 链上余额 {}, 已修正",
+        mint, state.balance, balance
+    );
+    state.balance = Some(balance);
+    if balance == 0 {
+        state.phase = PositionPhase::Closed;
+    }
+}
+
+// 执行卖出，返回需要稍后重试的退出
+async fn execute(mint: &Pubkey, mut order: ExitOrder) -> Option<ExitOrder> {
+    let (creator, balance, wallet, bonding_curve, migrated, kind) =
+        TOKEN_TABLE.get(mint).map(|state| {
+            (
+                state.token_creator,
+                state.balance.unwrap_or(0),
+                state.wallet,
+                state.bonding_curve,
+                state.migrated,
+                state.venue,
+            )
+        })?;
+    let Some(wallet) = wallet.and_then(|wallet| wallet_pool::wallet(&wallet)) else {
+        println!("[❌退出失败] {} 持仓钱包不在钱包池中", mint);
+        set_phase(mint, PositionPhase::Failed);
+        return Some(order);
+    };
+    // 重试的退出可能已被对账修正过余额
+    order.amount = if order.close {
+        balance
+    } else {
+        order.amount.min(balance)
+    };
+    if order.amount == 0 {
+        return None;
+    }
+    set_phase(mint, PositionPhase::Exiting);
+
+    println!(
+        "[出售代币🪙: {}] {}，卖出 {} 个代币",
+        mint, order.reason, order.amount
+    );
+
+    if order.blacklist {
+        tokio::spawn(async move {
+            if let Err(e) = add_to_blacklist(&creator.to_string()).await {
+                println!("[狗庄黑名单] 写入失败: {:?}", e);
+            }
+        });
+    }
+
+    // 卖出前的钱包余额，成交后按余额变化记录实际到手
+    let lamports_before = sol_balance::wallet_lamports(&wallet.pubkey());
+    for attempt in 1..=SELL_RETRIES {
+        let result = if migrated && kind != VenueKind::Pump {
+            Err(anyhow!("{:?} 毕业后的池子不支持卖出", kind))
+        } else if migrated {
+            sell_migrated(&wallet, mint, &order).await
+        } else {
+            sell_on_curve(&wallet, mint, creator, bonding_curve, kind, &order).await
+        };
+
+        match result {
+            Ok(_) => return settle(mint, &wallet.pubkey(), lamports_before, order).await,
+            Err(e) => {
+                println!("[❌卖出失败] {} 第 {} 次: {:?}", mint, attempt, e);
+                time::sleep(Duration::from_millis(300 * attempt as u64)).await;
+            }
+        }
+    }
+
+    println!(
+        "[❌退出失败] {} 已重试 {} 次，等待下次事件重试",
+        mint, SELL_RETRIES
+    );
+    set_phase(mint, PositionPhase::Failed);
+    Some(order)
+}
+
+// 按最新储备计算最少到手后在 bonding curve 卖出
+async fn sell_on_curve(
+    wallet: &Wallet,
+    mint: &Pubkey,
+    creator: Pubkey,
+    bonding_curve: Option<Pubkey>,
+    kind: VenueKind,
+    order: &ExitOrder,
+) -> Result<()> {
+    let curve = latest_curve(mint, bonding_curve, kind)
+        .await
+        .map_err(|e| anyhow!("无法报价: {}", e))?;
+    let venue = venue(kind);
+    let sell = CurveSell {
+        mint: *mint,
+        creator,
+        token_amount: order.amount,
+        min_sol_output: venue.sell_quote(&curve, order.amount, order.slippage_bps),
+    };
+    venue_sell_with_tip(wallet, venue, &sell, order.tip).await
+}
+
+// PUMP 代币迁移后在 PumpSwap 卖出，没有 PumpSwap 池子时 (旧版迁移或外部上线) 走 Raydium
+// 每次都重新读取池子储备报价
+async fn sell_migrated(wallet: &Wallet, mint: &Pubkey, order: &ExitOrder) -> Result<()> {
+    let pump_amm_error = match find_pump_amm_pool(mint).await {
+        Ok(pool) => {
+            let min_sol_output = pool
+                .state
+                .sell_quote_with_slippage(order.amount, order.slippage_bps);
+            return pump_amm_sell_with_tip(wallet, &pool, order.amount, min_sol_output, order.tip)
+                .await;
+        }
+        Err(e) => e,
+    };
+
+    let pool = find_raydium_pool(mint)
+        .await
+        .map_err(|e| anyhow!("{}; {}", pump_amm_error, e))?;
+    let min_sol_output = pool.sell_quote_with_slippage(order.amount, order.slippage_bps);
+    raydium_sell_with_tip(wallet, *mint, &pool, order.amount, min_sol_output, order.tip).await
+}
+
+// 卖出发送后按链上余额更新持仓，全部卖出但仍有余额时返回剩余部分的退出
+async fn settle(
+    mint: &Pubkey,
+    wallet: &Pubkey,
+    lamports_before: Option<u64>,
+    order: ExitOrder,
+) -> Option<ExitOrder> {
+    time::sleep(SETTLE_DELAY).await;
+    let on_chain = token_balance(wallet, mint).await;
+    let sol_delta = wallet_sol_delta(*wallet, lamports_before).await;
+
+    let mut state = TOKEN_TABLE.get_mut(mint)?;
+    let expected = state.balance.unwrap_or(0).saturating_sub(order.amount);
+    let balance = match on_chain {
+        Ok(balance) => {
+            if balance != expected {
+                println!(
+                    "[持仓] {} 链上余额 {} 与预期 {} 不一致，以链上为准",
+                    mint, balance, expected
+                );
+            }
+            balance
+        }
+        Err(e) => {
+            println!("[持仓] {} 查询余额失败，按预期 {} 记录: {:?}", mint, expected, e);
+            expected
+        }
+    };
+
+    let sold = state.balance.unwrap_or(0).saturating_sub(balance);
+    if sold == 0 {
+        // 没有成交，多半是价格跌破最少到手，下次按最新储备重新报价
+        if let Some(loosened) = order.loosened() {
+            println!(
+                "[持仓] {} 紧急卖出未成交, 滑点放宽到 {} 基点后重试",
+                mint, loosened.slippage_bps
+            );
+            state.phase = PositionPhase::Failed;
+            return Some(loosened);
+        }
+        println!("[持仓] {} 卖出未成交", mint);
+        if order.close {
+            state.phase = PositionPhase::Failed;
+            return Some(order);
+        }
+        state.phase = PositionPhase::Open;
+        return None;
+    }
+
+    let proceeds = match sol_delta {
+        Some(delta) if delta > 0.0 => delta,
+        _ => sol_value(sold, state.current_price.unwrap_or(0.0), state.venue),
+    };
+    risk::record_sell(*mint, proceeds);
+    state.balance = Some(balance);
+    state.sell_stage = order.stage;
+
+    if balance == 0 {
+        state.phase = PositionPhase::Closed;
+        return None;
+    }
+
+    if order.close {
+        // 全部卖出未完全成交
+        state.phase = PositionPhase::Failed;
+        return Some(ExitOrder {
+            amount: balance,
+            ..order
+        });
+    }
+
+    state.phase = PositionPhase::Open;
+    None
+}
+
+// 卖出报价用的储备: 优先用价格订阅的最新值，没有时从链上读取
+async fn latest_curve(
+    mint: &Pubkey,
+    bonding_curve: Option<Pubkey>,
+    kind: VenueKind,
+) -> Result<CurveState> {
+    if let Some(curve) = TOKEN_TABLE.get(mint).and_then(|state| state.curve) {
+        return Ok(curve);
+    }
+
+    let bonding_curve = bonding_curve.ok_or_else(|| anyhow!("没有 bonding curve 地址"))?;
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+    let account = app_state.client.get_account(&bonding_curve).await?;
+    let (curve, _) = venue(kind)
+        .decode_curve(&account.data)
+        .ok_or_else(|| anyhow!("bonding curve 解析失败"))?;
+    update_token_state(*mint, |state| state.curve = Some(curve));
+    Ok(curve)
+}
+
+// 钱包 SOL 余额相对 lamports_before 的变化 (SOL)，余额未知或没有变化时返回 None
+async fn wallet_sol_delta(wallet: Pubkey, lamports_before: Option<u64>) -> Option<f64> {
+    let before = lamports_before?;
+    let after = match sol_balance::refresh_wallet_lamports(wallet).await {
+        Ok(after) => after,
+        Err(e) => {
+            println!("[持仓] 查询钱包 {} 余额失败: {:?}", wallet, e);
+            return None;
+        }
+    };
+    (after != before).then(|| (after as f64 - before as f64) / LAMPORTS_PER_SOL)
+}
+
+// 按价格估算代币价值 (SOL)，拿不到钱包余额变化时使用
+fn sol_value(amount: u64, price: f64, kind: VenueKind) -> f64 {
+    amount as f64 / venue(kind).token_decimals() * price
+}
+
+pub async fn token_balance(wallet: &Pubkey, mint: &Pubkey) -> Result<u64> {
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+    let ata = get_associated_token_address(wallet, mint);
+
+    let account = get_account_info_fast(&app_state.client, mint, &ata)
+        .await
+        .map_err(|e| anyhow!("查询ATA失败: {:?}", e))?;
+    Ok(account.base.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_migrated_before_fill() {
+        // 迁移后清仓使用普通卖出小费
+        unsafe { std::env::set_var("ZERO_SLOT_SELL_FEE", "0.0001") };
+        let mint = Pubkey::new_unique();
+        update_token_state(mint, |state| {
+            state.phase = PositionPhase::Pending;
+            state.venue = VenueKind::Pump;
+        });
+
+        // 买入未成交时迁移，先只记录
+        assert!(on_migrated_event(&mint).is_none());
+        assert!(migrated(&mint));
+        assert_eq!(phase(&mint), Some(PositionPhase::Pending));
+
+        // 成交后直接清仓，不再等待不会到来的迁移事件
+        let order = on_filled(mint, 0.000_000_03, 1_000_000, None, None)
+            .await
+            .unwrap();
+        assert_eq!(order.amount, 1_000_000);
+        assert!(order.close);
+        assert_eq!(phase(&mint), Some(PositionPhase::Open));
+        assert!(evaluate(&mint, None).is_none());
+
+        TOKEN_TABLE.remove(&mint);
+        risk::record_close(&mint);
+    }
+}
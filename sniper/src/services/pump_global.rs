@@ -0,0 +1,222 @@
+use crate::api::APP_STATE;
+use crate::models::bonding_curve::{
+    FEE_CONFIG, FeeConfig, INITIAL_REAL_TOKEN_RESERVES, INITIAL_VIRTUAL_SOL_RESERVES,
+    INITIAL_VIRTUAL_TOKEN_RESERVES,
+};
+use crate::transaction::{FEE_RECIPIENT, GLOBAL_ACCOUNT};
+use anyhow::{Result, anyhow};
+use borsh::{BorshDeserialize, BorshSerialize};
+use futures_util::SinkExt;
+use grpc_client::{AppError, YellowstoneGrpc};
+use lazy_static::lazy_static;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tokio::time::{self, Duration};
+use tokio_stream::StreamExt;
+use yellowstone_grpc_proto::geyser::{
+    CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestPing,
+    subscribe_update::UpdateOneof,
+};
+
+// PUMP Global 账户: 初始储备、手续费、收费地址等全局参数
+pub const GLOBAL_DISCRIMINATOR: [u8; 8] = [167, 232, 232, 177, 200, 108, 114, 127];
+
+// 只解码需要的前缀字段，新版程序在末尾追加的字段忽略
+#[derive(Debug, Clone, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct PumpGlobal {
+    pub initialized: bool,
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+    pub fee_basis_points: u64,
+    pub withdraw_authority: Pubkey,
+    pub enable_migrate: bool,
+    pub pool_migration_fee: u64,
+    pub creator_fee_basis_points: u64,
+}
+
+impl Default for PumpGlobal {
+    fn default() -> Self {
+        let fees = FeeConfig::default();
+        Self {
+            initialized: true,
+            authority: Pubkey::default(),
+            fee_recipient: FEE_RECIPIENT,
+            initial_virtual_token_reserves: INITIAL_VIRTUAL_TOKEN_RESERVES,
+            initial_virtual_sol_reserves: INITIAL_VIRTUAL_SOL_RESERVES,
+            initial_real_token_reserves: INITIAL_REAL_TOKEN_RESERVES,
+            token_total_supply: 1_000_000_000_000_000,
+            fee_basis_points: fees.fee_basis_points,
+            withdraw_authority: Pubkey::default(),
+            enable_migrate: true,
+            pool_migration_fee: 0,
+            creator_fee_basis_points: fees.creator_fee_basis_points,
+        }
+    }
+}
+
+impl PumpGlobal {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != GLOBAL_DISCRIMINATOR {
+            return Err(anyhow!("Global 账户 discriminator 不匹配"));
+        }
+
+        let mut rest = &data[8..];
+        Ok(Self::deserialize(&mut rest)?)
+    }
+
+    pub fn fee_config(&self) -> FeeConfig {
+        FeeConfig {
+            fee_basis_points: self.fee_basis_points,
+            creator_fee_basis_points: self.creator_fee_basis_points,
+        }
+    }
+}
+
+lazy_static! {
+    // 最新的 Global 账户状态，启动拉取失败时使用默认值
+    pub static ref PUMP_GLOBAL: RwLock<PumpGlobal> = RwLock::new(PumpGlobal::default());
+}
+
+pub fn pump_global() -> PumpGlobal {
+    PUMP_GLOBAL.read().unwrap().clone()
+}
+
+pub fn fee_recipient() -> Pubkey {
+    PUMP_GLOBAL.read().unwrap().fee_recipient
+}
+
+fn update_pump_global(data: &[u8]) {
+    let global = match PumpGlobal::decode(data) {
+        Ok(global) => global,
+        Err(e) => {
+            println!("[Global] 解析失败: {:?}", e);
+            return;
+        }
+    };
+
+    if global == pump_global() {
+        return;
+    }
+
+    *FEE_CONFIG.write().unwrap() = global.fee_config();
+    *PUMP_GLOBAL.write().unwrap() = global.clone();
+    println!("[Global] 已更新: {:?}", global);
+}
+
+// 启动时拉取一次 Global 账户
+pub async fn load_pump_global() -> Result<()> {
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+
+    let account = app_state
+        .client
+        .get_account_with_commitment(&GLOBAL_ACCOUNT, CommitmentConfig::processed())
+        .await?
+        .value
+        .ok_or_else(|| anyhow!("Global 账户不存在"))?;
+
+    update_pump_global(&account.data);
+    Ok(())
+}
+
+// 订阅 Global 账户，参数变更时实时更新
+pub async fn watch_pump_global() -> Result<(), AppError> {
+    let url = std::env::var("YELLOWSTONE_GRPC_URL").expect("YELLOWSTONE_GRPC_URL must be set");
+    let grpc = YellowstoneGrpc::new(url.clone(), None);
+    let client = grpc.build_client().await?;
+
+    let subscribe_request = SubscribeRequest {
+        accounts: HashMap::from([(
+            "global".to_string(),
+            SubscribeRequestFilterAccounts {
+                account: vec![GLOBAL_ACCOUNT.to_string()],
+                owner: vec![],
+                filters: vec![],
+                nonempty_txn_signature: None,
+            },
+        )]),
+        commitment: Some(CommitmentLevel::Processed.into()),
+        ..Default::default()
+    };
+
+    const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+    loop {
+        let (mut subscribe_tx, mut stream) = match client
+            .lock()
+            .await
+            .subscribe_with_request(Some(subscribe_request.clone()))
+            .await
+        {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                println!("[Global] 订阅失败: {:?}", e);
+                time::sleep(RETRY_INTERVAL).await;
+                continue;
+            }
+        };
+
+        println!("[Global] 订阅成功: [{}]", url);
+
+        while let Some(message) = stream.next().await {
+            match message {
+                Ok(msg) => match msg.update_oneof {
+                    Some(UpdateOneof::Account(update)) => {
+                        if let Some(account) = update.account {
+                            update_pump_global(&account.data);
+                        }
+                    }
+                    Some(UpdateOneof::Ping(_)) => {
+                        let _ = subscribe_tx
+                            .send(SubscribeRequest {
+                                ping: Some(SubscribeRequestPing { id: 1 }),
+                                ..Default::default()
+                            })
+                            .await;
+                    }
+                    _ => {}
+                },
+                Err(e) => {
+                    println!("[Global] 订阅中断: {:?}", e);
+                    break;
+                }
+            }
+        }
+
+        // 断线期间可能错过更新，重连前用 RPC 补一次
+        if let Err(e) = load_pump_global().await {
+            println!("[Global] 拉取失败: {:?}", e);
+        }
+        time::sleep(RETRY_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_global() {
+        let global = PumpGlobal {
+            initial_virtual_sol_reserves: 42_000_000_000,
+            fee_basis_points: 93,
+            ..PumpGlobal::default()
+        };
+
+        let mut data = GLOBAL_DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(&global).unwrap());
+        // 末尾的新增字段不影响解码
+        data.extend([0u8; 64]);
+        assert_eq!(PumpGlobal::decode(&data).unwrap(), global);
+
+        data[0] = 0;
+        assert!(PumpGlobal::decode(&data).is_err());
+    }
+}
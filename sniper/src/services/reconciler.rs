@@ -0,0 +1,300 @@
+use crate::api::APP_STATE;
+use crate::config::{RECONCILE_ADOPT, RECONCILE_INTERVAL_SECS};
+use crate::models::bonding_curve::BondingCurveAccount;
+use crate::models::exit_strategy::EntrySource;
+use crate::models::venue::VenueKind;
+use crate::services::position::{self, PositionEvent, PositionPhase, open_position};
+use crate::services::transaction_processor::TOKEN_TABLE;
+use crate::services::wallet_pool;
+use crate::transaction::{PUMP_PROGRAM_ID, get_bonding_curve_account};
+use anyhow::{Result, anyhow};
+use dashmap::DashSet;
+use futures_util::SinkExt;
+use grpc_client::{AppError, YellowstoneGrpc};
+use once_cell::sync::Lazy;
+use serde_json::json;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcTokenAccountsFilter};
+use solana_client::rpc_request::RpcRequest;
+use solana_client::rpc_response::{RpcKeyedAccount, RpcResult};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account;
+use std::collections::HashMap;
+use tokio::time::{self, Duration};
+use tokio_stream::StreamExt;
+use yellowstone_grpc_proto::geyser::subscribe_request_filter_accounts_filter::Filter;
+use yellowstone_grpc_proto::geyser::subscribe_request_filter_accounts_filter_memcmp::Data;
+use yellowstone_grpc_proto::geyser::{
+    CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
+    SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp,
+    SubscribeRequestPing, subscribe_update::UpdateOneof,
+};
+
+// 不能接管的代币（已迁移或不是 pump 代币），避免每轮重复查询
+static SKIPPED: Lazy<DashSet<Pubkey>> = Lazy::new(DashSet::new);
+
+// 钱包里各 mint 的 ATA 余额，卖出只走 ATA，其它 token 账户不计入
+pub async fn wallet_balances(wallet: &Pubkey) -> Result<HashMap<Pubkey, u64>> {
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        data_slice: None,
+        min_context_slot: None,
+    };
+
+    let token_accounts: RpcResult<Vec<RpcKeyedAccount>> = app_state
+        .client
+        .send(
+            RpcRequest::GetTokenAccountsByOwner,
+            json!([
+                wallet.to_string(),
+                RpcTokenAccountsFilter::ProgramId(spl_token::id().to_string()),
+                config
+            ]),
+        )
+        .await;
+
+    let mut balances = HashMap::new();
+    for keyed in token_accounts?.value {
+        let Some(data) = keyed.account.data.decode() else {
+            continue;
+        };
+        let Ok(account) = Account::unpack(&data) else {
+            continue;
+        };
+        if keyed.pubkey != get_associated_token_address(wallet, &account.mint).to_string() {
+            continue;
+        }
+        balances.insert(account.mint, account.amount);
+    }
+
+    Ok(balances)
+}
+
+// 逐个钱包对账
+pub async fn reconcile_once() -> Result<()> {
+    for wallet in wallet_pool::wallets() {
+        reconcile_wallet(&wallet.pubkey()).await?;
+    }
+    Ok(())
+}
+
+// 对比该钱包跟踪中的持仓和链上余额，余额修正交给持仓的 owner 任务
+async fn reconcile_wallet(wallet: &Pubkey) -> Result<()> {
+    let balances = wallet_balances(wallet).await?;
+
+    let tracked = TOKEN_TABLE
+        .iter()
+        .filter(|entry| {
+            entry.wallet == Some(*wallet)
+                && matches!(entry.phase, PositionPhase::Open | PositionPhase::Failed)
+        })
+        .map(|entry| (*entry.key(), entry.balance))
+        .collect::<Vec<_>>();
+
+    for (mint, balance) in tracked {
+        let on_chain = balances.get(&mint).copied().unwrap_or(0);
+        if balance != Some(on_chain) {
+            println!(
+                "[对账] {} 余额不一致, 记录 {:?}, 链上 {}",
+                mint, balance, on_chain
+            );
+            position::notify(mint, PositionEvent::Reconcile(on_chain));
+        }
+    }
+
+    if *RECONCILE_ADOPT {
+        for (mint, amount) in balances {
+            if amount == 0 || TOKEN_TABLE.contains_key(&mint) || SKIPPED.contains(&mint) {
+                continue;
+            }
+            match adopt(*wallet, mint, amount).await {
+                Ok(Adoption::Adopted) => {}
+                Ok(Adoption::Skip(reason)) => {
+                    println!("[对账] {} 无法接管: {}", mint, reason);
+                    SKIPPED.insert(mint);
+                }
+                // RPC 等临时错误，下一轮对账重试
+                Err(e) => println!("[对账] {} 接管失败, 下一轮重试: {:?}", mint, e),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// 接管结果，Skip 为确定无法接管的代币，之后不再尝试
+enum Adoption {
+    Adopted,
+    Skip(&'static str),
+}
+
+// 钱包里未被跟踪的 pump 代币，按当前价登记为持仓
+async fn adopt(wallet: Pubkey, mint: Pubkey, amount: u64) -> Result<Adoption> {
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+    let (bonding_curve, _) = get_bonding_curve_account(&mint, &PUMP_PROGRAM_ID).await?;
+
+    let Some(account) = app_state
+        .client
+        .get_account_with_commitment(&bonding_curve, CommitmentConfig::processed())
+        .await?
+        .value
+    else {
+        return Ok(Adoption::Skip("不是 pump 代币"));
+    };
+    let Some(curve) = BondingCurveAccount::decode(&account.data) else {
+        return Ok(Adoption::Skip("bonding curve 解析失败"));
+    };
+    if curve.complete {
+        return Ok(Adoption::Skip("已迁移"));
+    }
+
+    let price = curve.curve().price();
+    println!(
+        "[对账] 接管钱包 {} 中未跟踪的代币 {}, 余额 {}, 当前价 {:.12}",
+        wallet, mint, amount, price
+    );
+    open_position(
+        mint,
+        bonding_curve,
+        curve.creator,
+        wallet,
+        price,
+        amount,
+        EntrySource::Adopted,
+        VenueKind::Pump,
+    );
+
+    Ok(Adoption::Adopted)
+}
+
+pub async fn run_reconciler() {
+    let interval = Duration::from_secs(*RECONCILE_INTERVAL_SECS);
+    loop {
+        time::sleep(interval).await;
+
+        if let Err(e) = reconcile_once().await {
+            println!("[对账] 失败: {:?}", e);
+        }
+    }
+}
+
+// 某个钱包名下的 spl-token 账户
+fn wallet_tokens_filter(wallet: &Pubkey) -> SubscribeRequestFilterAccounts {
+    SubscribeRequestFilterAccounts {
+        account: vec![],
+        owner: vec![spl_token::id().to_string()],
+        filters: vec![
+            SubscribeRequestFilterAccountsFilter {
+                filter: Some(Filter::Datasize(Account::LEN as u64)),
+            },
+            // token 账户的 owner 字段在 32 偏移处
+            SubscribeRequestFilterAccountsFilter {
+                filter: Some(Filter::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+                    offset: 32,
+                    data: Some(Data::Bytes(wallet.to_bytes().to_vec())),
+                })),
+            },
+        ],
+        nonempty_txn_signature: None,
+    }
+}
+
+// 订阅钱包池所有钱包的 token 账户，余额变化即时对账
+pub async fn watch_wallet_accounts() -> Result<(), AppError> {
+    let url = std::env::var("YELLOWSTONE_GRPC_URL").expect("YELLOWSTONE_GRPC_URL must be set");
+    let grpc = YellowstoneGrpc::new(url.clone(), None);
+    let client = grpc.build_client().await?;
+
+    let accounts = wallet_pool::wallets()
+        .iter()
+        .map(|wallet| {
+            (
+                format!("wallet_tokens_{}", wallet.pubkey()),
+                wallet_tokens_filter(&wallet.pubkey()),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    let subscribe_request = SubscribeRequest {
+        accounts,
+        commitment: Some(CommitmentLevel::Processed.into()),
+        ..Default::default()
+    };
+
+    const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+    loop {
+        let (mut subscribe_tx, mut stream) = match client
+            .lock()
+            .await
+            .subscribe_with_request(Some(subscribe_request.clone()))
+            .await
+        {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                println!("[对账] 订阅失败: {:?}", e);
+                time::sleep(RETRY_INTERVAL).await;
+                continue;
+            }
+        };
+
+        println!("[对账] 订阅钱包 token 账户成功: [{}]", url);
+
+        while let Some(message) = stream.next().await {
+            match message {
+                Ok(msg) => match msg.update_oneof {
+                    Some(UpdateOneof::Account(update)) => {
+                        // 同一个 mint 可能在别的钱包里也有余额，只处理持仓所在的钱包
+                        // 和全量对账一致，只认 ATA 的余额
+                        if let Some(account) = update.account
+                            && let Ok(token_account) = Account::unpack(&account.data)
+                            && Pubkey::try_from(account.pubkey.as_slice()).is_ok_and(|pubkey| {
+                                pubkey
+                                    == get_associated_token_address(
+                                        &token_account.owner,
+                                        &token_account.mint,
+                                    )
+                            })
+                            && TOKEN_TABLE
+                                .get(&token_account.mint)
+                                .is_some_and(|state| state.wallet == Some(token_account.owner))
+                        {
+                            position::notify(
+                                token_account.mint,
+                                PositionEvent::Reconcile(token_account.amount),
+                            );
+                        }
+                    }
+                    Some(UpdateOneof::Ping(_)) => {
+                        let _ = subscribe_tx
+                            .send(SubscribeRequest {
+                                ping: Some(SubscribeRequestPing { id: 1 }),
+                                ..Default::default()
+                            })
+                            .await;
+                    }
+                    _ => {}
+                },
+                Err(e) => {
+                    println!("[对账] 订阅中断: {:?}", e);
+                    break;
+                }
+            }
+        }
+
+        // 断线期间可能错过变化，重连前全量对账一次
+        if let Err(e) = reconcile_once().await {
+            println!("[对账] 失败: {:?}", e);
+        }
+        time::sleep(RETRY_INTERVAL).await;
+    }
+}
@@ -0,0 +1,370 @@
+use crate::config::{
+    BUY_ENABLED, RISK_CREATOR_COOLDOWN_SECS, RISK_DAILY_LOSS_LIMIT, RISK_MAX_BUYS_PER_MINUTE,
+    RISK_MAX_OPEN_POSITIONS, RISK_MAX_SOL_DEPLOYED,
+};
+use crate::models::exit_strategy::EntrySource;
+use crate::services::wallet_pool::{self, Wallet};
+use chrono::{Local, NaiveDate};
+use once_cell::sync::Lazy;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// 买入前的风控: 持仓数、资金占用、买入频率、当日亏损、同一开发者冷却
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskLimits {
+    pub max_open_positions: usize,
+    // SOL
+    pub max_sol_deployed: f64,
+    pub max_buys_per_minute: usize,
+    // SOL，当日已实现亏损达到后停止买入
+    pub daily_loss_limit: f64,
+    pub creator_cooldown: Duration,
+}
+
+impl RiskLimits {
+    pub fn from_config() -> Self {
+        Self {
+            max_open_positions: *RISK_MAX_OPEN_POSITIONS,
+            max_sol_deployed: *RISK_MAX_SOL_DEPLOYED,
+            max_buys_per_minute: *RISK_MAX_BUYS_PER_MINUTE,
+            daily_loss_limit: *RISK_DAILY_LOSS_LIMIT,
+            creator_cooldown: Duration::from_secs(*RISK_CREATOR_COOLDOWN_SECS),
+        }
+    }
+}
+
+// 单个持仓的资金占用 (SOL，按买卖前后的钱包余额变化记录)
+#[derive(Debug, Clone, Default)]
+struct Exposure {
+    // 持有该代币的钱包
+    wallet: Pubkey,
+    // 已发出买入、尚未成交时按最多投入计算
+    reserved: f64,
+    cost: f64,
+    proceeds: f64,
+}
+
+impl Exposure {
+    fn deployed(&self) -> f64 {
+        (self.reserved + self.cost - self.proceeds).max(0.0)
+    }
+}
+
+#[derive(Debug)]
+pub struct RiskState {
+    limits: RiskLimits,
+    buy_times: VecDeque<Instant>,
+    creator_last_buy: HashMap<Pubkey, Instant>,
+    exposures: HashMap<Pubkey, Exposure>,
+    day: NaiveDate,
+    realized_today: f64,
+    // 触发自动停止买入的原因，手动重新开启买入后清除
+    halted: Option<String>,
+    last_rejection: Option<String>,
+}
+
+impl RiskState {
+    pub fn new(limits: RiskLimits) -> Self {
+        Self {
+            limits,
+            buy_times: VecDeque::new(),
+            creator_last_buy: HashMap::new(),
+            exposures: HashMap::new(),
+            day: Local::now().date_naive(),
+            realized_today: 0.0,
+            halted: None,
+            last_rejection: None,
+        }
+    }
+
+    fn roll_day(&mut self, today: NaiveDate) {
+        if today != self.day {
+            self.day = today;
+            self.realized_today = 0.0;
+        }
+    }
+
+    fn deployed(&self) -> f64 {
+        self.exposures.values().map(Exposure::deployed).sum()
+    }
+
+    // 每个钱包的资金占用，用于选择钱包
+    fn deployed_by_wallet(&self) -> HashMap<Pubkey, f64> {
+        let mut deployed = HashMap::new();
+        for exposure in self.exposures.values() {
+            *deployed.entry(exposure.wallet).or_insert(0.0) += exposure.deployed();
+        }
+        deployed
+    }
+
+    // sol_amount: 本次买入最多投入的 SOL
+    pub fn check_buy(
+        &mut self,
+        mint: &Pubkey,
+        creator: &Pubkey,
+        sol_amount: f64,
+        now: Instant,
+    ) -> Result<(), String> {
+        if let Some(reason) = &self.halted {
+            return Err(format!("已停止买入: {}", reason));
+        }
+
+        while self
+            .buy_times
+            .front()
+            .is_some_and(|time| now.duration_since(*time) >= Duration::from_secs(60))
+        {
+            self.buy_times.pop_front();
+        }
+
+        // 同一 mint 只允许一个钱包持有，避免覆盖已有持仓的钱包和成本
+        let result = if self.exposures.contains_key(mint) {
+            Err(format!("{} 已有持仓或买入中", mint))
+        } else if self.exposures.len() >= self.limits.max_open_positions {
+            Err(format!("持仓数已达上限 {}", self.limits.max_open_positions))
+        } else if self.deployed() + sol_amount > self.limits.max_sol_deployed {
+            Err(format!(
+                "资金占用 {:.4} + {:.4} SOL 超过上限 {} SOL",
+                self.deployed(),
+                sol_amount,
+                self.limits.max_sol_deployed
+            ))
+        } else if self.buy_times.len() >= self.limits.max_buys_per_minute {
+            Err(format!("每分钟买入次数已达上限 {}", self.limits.max_buys_per_minute))
+        } else if self
+            .creator_last_buy
+            .get(creator)
+            .is_some_and(|time| now.duration_since(*time) < self.limits.creator_cooldown)
+        {
+            Err(format!("开发者 {} 冷却中", creator))
+        } else {
+            Ok(())
+        };
+
+        if let Err(reason) = &result {
+            self.last_rejection = Some(reason.clone());
+        }
+        result
+    }
+
+    // 发出买入即计入持仓数、频率和冷却，成交后再登记成本
+    pub fn record_buy(
+        &mut self,
+        mint: Pubkey,
+        creator: Pubkey,
+        wallet: Pubkey,
+        sol_amount: f64,
+        now: Instant,
+    ) {
+        let exposure = self.exposures.entry(mint).or_default();
+        exposure.wallet = wallet;
+        exposure.reserved = sol_amount;
+        self.buy_times.push_back(now);
+        self.creator_last_buy.insert(creator, now);
+        self.creator_last_buy
+            .retain(|_, time| now.duration_since(*time) < self.limits.creator_cooldown);
+    }
+
+    // 买入没有发出时撤销预占，已成交的持仓不受影响
+    pub fn release_buy(&mut self, mint: &Pubkey) {
+        if self
+            .exposures
+            .get(mint)
+            .is_some_and(|exposure| exposure.cost == 0.0 && exposure.proceeds == 0.0)
+        {
+            self.exposures.remove(mint);
+        }
+    }
+
+    pub fn record_fill(&mut self, mint: Pubkey, wallet: Pubkey, cost: f64) {
+        let exposure = self.exposures.entry(mint).or_default();
+        exposure.wallet = wallet;
+        exposure.reserved = 0.0;
+        exposure.cost += cost;
+    }
+
+    pub fn record_sell(&mut self, mint: Pubkey, proceeds: f64) {
+        self.exposures.entry(mint).or_default().proceeds += proceeds;
+    }
+
+    // 平仓时结算盈亏，当日亏损超限则返回停止原因
+    pub fn record_close(&mut self, mint: &Pubkey, today: NaiveDate) -> Option<String> {
+        let exposure = self.exposures.remove(mint)?;
+        self.roll_day(today);
+        self.realized_today += exposure.proceeds - exposure.cost;
+
+        if self.halted.is_none() && -self.realized_today >= self.limits.daily_loss_limit {
+            let reason = format!(
+                "当日亏损 {:.4} SOL 达到上限 {} SOL",
+                -self.realized_today, self.limits.daily_loss_limit
+            );
+            self.halted = Some(reason.clone());
+            return Some(reason);
+        }
+        None
+    }
+}
+
+static RISK: Lazy<Mutex<RiskState>> =
+    Lazy::new(|| Mutex::new(RiskState::new(RiskLimits::from_config())));
+
+// 买入前调用，风控通过后再选出余额足够的钱包 (被拒绝的买入不推进轮询)，计入频率和冷却
+pub fn try_reserve_buy(
+    mint: Pubkey,
+    creator: Pubkey,
+    source: EntrySource,
+    sol_amount: f64,
+) -> Result<Arc<Wallet>, String> {
+    let now = Instant::now();
+    let mut risk = RISK.lock().unwrap();
+    risk.roll_day(Local::now().date_naive());
+    risk.check_buy(&mint, &creator, sol_amount, now)?;
+    let wallet = match wallet_pool::select_wallet(source, sol_amount, &risk.deployed_by_wallet()) {
+        Ok(wallet) => wallet,
+        Err(reason) => {
+            risk.last_rejection = Some(reason.clone());
+            return Err(reason);
+        }
+    };
+    risk.record_buy(mint, creator, wallet.pubkey(), sol_amount, now);
+    Ok(wallet)
+}
+
+pub fn release_buy(mint: &Pubkey) {
+    RISK.lock().unwrap().release_buy(mint);
+}
+
+pub fn record_fill(mint: Pubkey, wallet: Pubkey, cost: f64) {
+    RISK.lock().unwrap().record_fill(mint, wallet, cost);
+}
+
+pub fn record_sell(mint: Pubkey, proceeds: f64) {
+    RISK.lock().unwrap().record_sell(mint, proceeds);
+}
+
+pub fn record_close(mint: &Pubkey) {
+    let halted = RISK
+        .lock()
+        .unwrap()
+        .record_close(mint, Local::now().date_naive());
+
+    if let Some(reason) = halted {
+        println!("[风控] 自动停止买入: {}", reason);
+        unsafe {
+            *BUY_ENABLED = false;
+        }
+    }
+}
+
+// 手动重新开启买入
+pub fn resume() {
+    let mut risk = RISK.lock().unwrap();
+    if let Some(reason) = risk.halted.take() {
+        println!("[风控] 已恢复买入, 之前停止原因: {}", reason);
+    }
+    risk.last_rejection = None;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RiskStatus {
+    pub halted: Option<String>,
+    pub last_rejection: Option<String>,
+    pub open_positions: usize,
+    pub sol_deployed: f64,
+    pub buys_last_minute: usize,
+    pub realized_today: f64,
+}
+
+pub fn status() -> RiskStatus {
+    let now = Instant::now();
+    let risk = RISK.lock().unwrap();
+    RiskStatus {
+        halted: risk.halted.clone(),
+        last_rejection: risk.last_rejection.clone(),
+        open_positions: risk.exposures.len(),
+        sol_deployed: risk.deployed(),
+        buys_last_minute: risk
+            .buy_times
+            .iter()
+            .filter(|time| now.duration_since(**time) < Duration::from_secs(60))
+            .count(),
+        realized_today: risk.realized_today,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> RiskLimits {
+        RiskLimits {
+            max_open_positions: 2,
+            max_sol_deployed: 0.5,
+            max_buys_per_minute: 3,
+            daily_loss_limit: 0.1,
+            creator_cooldown: Duration::from_secs(600),
+        }
+    }
+
+    #[test]
+    fn test_buy_limits() {
+        let mut risk = RiskState::new(limits());
+        let now = Instant::now();
+        let creator = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        assert!(risk.check_buy(&mint, &creator, 0.1, now).is_ok());
+        risk.record_buy(mint, creator, Pubkey::new_unique(), 0.1, now);
+        // 同一开发者冷却
+        assert!(risk.check_buy(&Pubkey::new_unique(), &creator, 0.1, now).is_err());
+        // 同一 mint 已在买入中
+        assert!(risk.check_buy(&mint, &Pubkey::new_unique(), 0.1, now).is_err());
+
+        // 资金占用超限（未成交的买入按最多投入计算）
+        assert!(risk.check_buy(&Pubkey::new_unique(), &Pubkey::new_unique(), 0.45, now).is_err());
+        assert!(risk.check_buy(&Pubkey::new_unique(), &Pubkey::new_unique(), 0.1, now).is_ok());
+
+        risk.record_fill(Pubkey::new_unique(), Pubkey::new_unique(), 0.1);
+        // 持仓数超限
+        assert!(risk.check_buy(&Pubkey::new_unique(), &Pubkey::new_unique(), 0.01, now).is_err());
+    }
+
+    #[test]
+    fn test_daily_loss_halts() {
+        let mut risk = RiskState::new(limits());
+        let today = Local::now().date_naive();
+        let mint = Pubkey::new_unique();
+
+        risk.record_fill(mint, Pubkey::new_unique(), 0.2);
+        risk.record_sell(mint, 0.05);
+        assert!(risk.record_close(&mint, today).is_some());
+        assert!(
+            risk.check_buy(&Pubkey::new_unique(), &Pubkey::new_unique(), 0.01, Instant::now())
+                .is_err()
+        );
+
+        // 隔天清零，但停止状态需要手动恢复
+        risk.roll_day(today.succ_opt().unwrap());
+        assert_eq!(risk.realized_today, 0.0);
+        assert!(risk.halted.is_some());
+    }
+
+    #[test]
+    fn test_release_buy() {
+        let mut risk = RiskState::new(limits());
+        let now = Instant::now();
+        let mint = Pubkey::new_unique();
+
+        risk.record_buy(mint, Pubkey::new_unique(), Pubkey::new_unique(), 0.1, now);
+        risk.release_buy(&mint);
+        assert!(risk.exposures.is_empty());
+
+        // 已成交的持仓不能被撤销
+        risk.record_buy(mint, Pubkey::new_unique(), Pubkey::new_unique(), 0.1, now);
+        risk.record_fill(mint, Pubkey::new_unique(), 0.1);
+        risk.release_buy(&mint);
+        assert!(risk.exposures.contains_key(&mint));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::config;
+use crate::services::wallet_pool::{self, Wallet};
+use crate::transaction::{GLOBAL_ACCOUNT, PROXY_PROGRAM, PUMP_PROGRAM_ID};
+use crate::tx::{JITO_TIP_ACCOUNTS, ZEROSLOT_TIP_ACCOUNTS};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+// 启动自检: 任何一项失败都拒绝启动，避免用错钱包或配置错误时静默不买入
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    checks: Vec<(String, Result<String, String>)>,
+}
+
+impl CheckReport {
+    fn push(&mut self, name: impl Into<String>, result: Result<String, String>) {
+        self.checks.push((name.into(), result));
+    }
+
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|(_, result)| result.is_ok())
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, result) in &self.checks {
+            match result {
+                Ok(detail) => writeln!(f, "  ✅ {}: {}", name, detail)?,
+                Err(reason) => writeln!(f, "  ❌ {}: {}", name, reason)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// 配置的钱包地址必须与私钥一致
+fn check_signer(wallet: &Wallet) -> Result<String, String> {
+    let signer = wallet.pubkey();
+    // 空字符串与未设置相同
+    let declared = wallet
+        .declared_pubkey
+        .as_deref()
+        .filter(|declared| !declared.trim().is_empty());
+    match declared {
+        None => Ok(format!("{} (由私钥推导)", signer)),
+        Some(declared) if declared == signer.to_string() => Ok(signer.to_string()),
+        Some(declared) => Err(format!(
+            "配置的地址 {} 与私钥对应的地址 {} 不一致",
+            declared, signer
+        )),
+    }
+}
+
+fn check_nonce(wallet: &Wallet, account: Option<&Account>) -> Result<String, String> {
+    let account = account.ok_or_else(|| format!("nonce 账户 {} 不存在", wallet.nonce))?;
+    let data = solana_rpc_client_nonce_utils::data_from_account(account)
+        .map_err(|e| format!("nonce 账户 {} 无效: {}", wallet.nonce, e))?;
+    if data.authority != wallet.pubkey() {
+        return Err(format!(
+            "nonce 账户 {} 的 authority 是 {}, 不是签名钱包 {}",
+            wallet.nonce,
+            data.authority,
+            wallet.pubkey()
+        ));
+    }
+    Ok(wallet.nonce.to_string())
+}
+
+// 小费账户不能重复，也不能是自己的钱包
+fn check_tip_accounts(name: &str, tips: &[Pubkey], wallets: &HashSet<Pubkey>) -> Result<String, String> {
+    let mut seen = HashSet::new();
+    for tip in tips {
+        if *tip == Pubkey::default() || wallets.contains(tip) {
+            return Err(format!("{} 小费账户 {} 无效", name, tip));
+        }
+        if !seen.insert(tip) {
+            return Err(format!("{} 小费账户 {} 重复", name, tip));
+        }
+    }
+    Ok(format!("{} 个", tips.len()))
+}
+
+fn check_program(
+    name: &str,
+    program_id: &Pubkey,
+    account: Option<&Account>,
+) -> Result<String, String> {
+    match account {
+        Some(account) if account.executable => Ok(program_id.to_string()),
+        Some(_) => Err(format!("{} {} 不是可执行程序", name, program_id)),
+        None => Err(format!("{} {} 不存在", name, program_id)),
+    }
+}
+
+pub async fn run_self_check(client: &RpcClient) -> CheckReport {
+    let mut report = CheckReport::default();
+    let wallets = wallet_pool::wallets();
+
+    for wallet in wallets {
+        report.push(format!("钱包 {}", wallet.name), check_signer(wallet));
+    }
+
+    let mut nonces = HashSet::new();
+    for wallet in wallets {
+        if !nonces.insert(wallet.nonce) {
+            report.push(
+                format!("钱包 {} nonce", wallet.name),
+                Err(format!("nonce 账户 {} 被多个钱包共用", wallet.nonce)),
+            );
+        }
+    }
+
+    let wallet_keys = wallets.iter().map(|wallet| wallet.pubkey()).collect();
+    report.push("Jito 小费账户", check_tip_accounts("Jito", &JITO_TIP_ACCOUNTS[..], &wallet_keys));
+    report.push("0slot 小费账户", check_tip_accounts("0slot", &ZEROSLOT_TIP_ACCOUNTS[..], &wallet_keys));
+
+    report.push(
+        "PUMP 程序 ID",
+        if Pubkey::from_str(config::PUMP_PROGRAM_ID) == Ok(PUMP_PROGRAM_ID) {
+            Ok(PUMP_PROGRAM_ID.to_string())
+        } else {
+            Err(format!(
+                "config 中的 {} 与交易使用的 {} 不一致",
+                config::PUMP_PROGRAM_ID,
+                PUMP_PROGRAM_ID
+            ))
+        },
+    );
+
+    // 链上检查: RPC、nonce 账户、程序和 Global 账户一次查询
+    let mut keys = wallets.iter().map(|wallet| wallet.nonce).collect::<Vec<_>>();
+    keys.extend([PUMP_PROGRAM_ID, PROXY_PROGRAM, GLOBAL_ACCOUNT]);
+    let accounts = match client
+        .get_multiple_accounts_with_commitment(&keys, CommitmentConfig::confirmed())
+        .await
+    {
+        Ok(response) => {
+            report.push("RPC", Ok(format!("slot {}", response.context.slot)));
+            response.value
+        }
+        Err(e) => {
+            report.push("RPC", Err(format!("无法访问: {}", e)));
+            return report;
+        }
+    };
+
+    for (wallet, account) in wallets.iter().zip(&accounts) {
+        report.push(format!("钱包 {} nonce", wallet.name), check_nonce(wallet, account.as_ref()));
+    }
+
+    let programs = &accounts[wallets.len()..];
+    report.push("PUMP 程序", check_program("PUMP 程序", &PUMP_PROGRAM_ID, programs[0].as_ref()));
+    report.push("代理程序", check_program("代理程序", &PROXY_PROGRAM, programs[1].as_ref()));
+    report.push(
+        "PUMP Global",
+        match programs[2].as_ref() {
+            Some(account) if account.owner == PUMP_PROGRAM_ID => Ok(GLOBAL_ACCOUNT.to_string()),
+            Some(account) => Err(format!("owner 是 {}, 不是 PUMP 程序", account.owner)),
+            None => Err(format!("{} 不存在", GLOBAL_ACCOUNT)),
+        },
+    );
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    #[test]
+    fn test_signer_and_tip_checks() {
+        let keypair = Keypair::new();
+        let mut wallet = Wallet {
+            name: "test".to_string(),
+            declared_pubkey: Some(keypair.pubkey().to_string()),
+            keypair: Arc::new(keypair),
+            nonce: Pubkey::new_unique(),
+            dedicated: vec![],
+            blockhash: RwLock::new(Default::default()),
+        };
+        assert!(check_signer(&wallet).is_ok());
+        wallet.declared_pubkey = Some(String::new());
+        assert!(check_signer(&wallet).is_ok());
+        wallet.declared_pubkey = Some(Pubkey::new_unique().to_string());
+        assert!(check_signer(&wallet).is_err());
+
+        let tip = Pubkey::new_unique();
+        let wallets = HashSet::from([wallet.pubkey()]);
+        assert!(check_tip_accounts("Jito", &[tip, Pubkey::new_unique()], &wallets).is_ok());
+        assert!(check_tip_accounts("Jito", &[tip, tip], &wallets).is_err());
+        assert!(check_tip_accounts("Jito", &[wallet.pubkey()], &wallets).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::api::APP_STATE;
+use crate::config::{
+    JITO_FEE, SOL_BALANCE_POLL_MS, SOL_PAUSE_THRESHOLD, SOL_RESERVE, ZERO_SLOT_BUY_FEE,
+};
+use crate::models::bonding_curve::LAMPORTS_PER_SOL;
+use crate::services::wallet_pool;
+use anyhow::{Result, anyhow};
+use dashmap::{DashMap, DashSet};
+use once_cell::sync::Lazy;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use tokio::time::{self, Duration};
+
+// 新建 ATA 的租金
+const ATA_RENT_LAMPORTS: u64 = 2_039_280;
+// 交易基础费用，按两笔签名估算
+const TX_FEE_LAMPORTS: u64 = 10_000;
+
+// 每个钱包的 SOL 余额缓存，没有记录表示还没有拉取过
+static WALLET_LAMPORTS: Lazy<DashMap<Pubkey, u64>> = Lazy::new(DashMap::new);
+// 余额低于阈值的钱包暂停买入，余额恢复后自动解除
+static LOW_BALANCE: Lazy<DashSet<Pubkey>> = Lazy::new(DashSet::new);
+
+pub fn wallet_lamports(wallet: &Pubkey) -> Option<u64> {
+    WALLET_LAMPORTS.get(wallet).map(|lamports| *lamports)
+}
+
+// 钱包池的 SOL 余额合计
+pub fn total_lamports() -> u64 {
+    WALLET_LAMPORTS.iter().map(|entry| *entry.value()).sum()
+}
+
+pub fn wallet_paused(wallet: &Pubkey) -> bool {
+    LOW_BALANCE.contains(wallet)
+}
+
+// 所有钱包都低于阈值时买入完全暂停
+pub fn low_balance_paused() -> bool {
+    wallet_pool::wallets()
+        .iter()
+        .all(|wallet| wallet_paused(&wallet.pubkey()))
+}
+
+// 一次买入最多花费: 投入 + 两路小费 + ATA 租金 + 交易费
+pub fn buy_cost_lamports(sol_amount: f64) -> u64 {
+    let tips = unsafe { *JITO_FEE + *ZERO_SLOT_BUY_FEE };
+    ((sol_amount + tips) * LAMPORTS_PER_SOL) as u64 + ATA_RENT_LAMPORTS + TX_FEE_LAMPORTS
+}
+
+// 买入后余额不能低于保留金额
+pub fn check_affordable(wallet: &Pubkey, sol_amount: f64) -> Result<(), String> {
+    if wallet_paused(wallet) {
+        return Err(format!(
+            "钱包 {} 余额低于 {} SOL，暂停买入",
+            wallet, *SOL_PAUSE_THRESHOLD
+        ));
+    }
+
+    let Some(lamports) = wallet_lamports(wallet) else {
+        return Err(format!("钱包 {} 余额未知", wallet));
+    };
+
+    let cost = buy_cost_lamports(sol_amount);
+    let reserve = (*SOL_RESERVE * LAMPORTS_PER_SOL) as u64;
+    if lamports < cost + reserve {
+        return Err(format!(
+            "钱包 {} 余额 {:.4} SOL 不足, 买入需要 {:.4} SOL, 保留 {} SOL",
+            wallet,
+            lamports as f64 / LAMPORTS_PER_SOL,
+            cost as f64 / LAMPORTS_PER_SOL,
+            *SOL_RESERVE
+        ));
+    }
+
+    Ok(())
+}
+
+fn update_wallet_lamports(wallet: Pubkey, lamports: u64) {
+    WALLET_LAMPORTS.insert(wallet, lamports);
+
+    let threshold = (*SOL_PAUSE_THRESHOLD * LAMPORTS_PER_SOL) as u64;
+    if lamports < threshold {
+        if LOW_BALANCE.insert(wallet) {
+            println!(
+                "[余额] 钱包 {} 余额 {:.4} SOL 低于 {} SOL, 暂停买入",
+                wallet,
+                lamports as f64 / LAMPORTS_PER_SOL,
+                *SOL_PAUSE_THRESHOLD
+            );
+        }
+    } else if LOW_BALANCE.remove(&wallet).is_some() {
+        println!(
+            "[余额] 钱包 {} 余额恢复到 {:.4} SOL, 恢复买入",
+            wallet,
+            lamports as f64 / LAMPORTS_PER_SOL
+        );
+    }
+}
+
+pub async fn refresh_wallet_lamports(wallet: Pubkey) -> Result<u64> {
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+
+    let lamports = app_state
+        .client
+        .get_balance_with_commitment(&wallet, CommitmentConfig::processed())
+        .await?
+        .value;
+    update_wallet_lamports(wallet, lamports);
+    Ok(lamports)
+}
+
+pub async fn poll_wallet_lamports() {
+    let interval = Duration::from_millis(*SOL_BALANCE_POLL_MS);
+    loop {
+        for wallet in wallet_pool::wallets() {
+            if let Err(e) = refresh_wallet_lamports(wallet.pubkey()).await {
+                println!("[余额] 查询钱包 {} 余额失败: {:?}", wallet.name, e);
+            }
+        }
+
+        time::sleep(interval).await;
+    }
+}
@@ -1,21 +1,30 @@
 use crate::api::APP_STATE;
 use crate::api::get_account_info_fast;
-use crate::config::{BUY_ENABLED, PUBLIC_KEY, WHITELIST_ENABLED};
-use crate::models::pump_parser::PumpInstructionType;
-use crate::models::{PumpParser, TransactionResults};
+use crate::config::{BUY_ENABLED, COPY_TRADE_ENABLED, MAX_SOL, METADATA_SCREEN_ENABLED};
+use crate::models::TransactionResults;
+use crate::models::bonding_curve::CurveState;
+use crate::models::venue::{self, LaunchBuy, VenueKind, VENUES};
+use crate::models::exit_strategy::EntrySource;
 use crate::monitor::GRPC_NORMAL;
-use crate::transaction::{pump_buy, pump_sell};
+use crate::services::position::{self, PositionPhase, open_position};
+use crate::services::{alt_cache, copy_trade, dev_exit, risk};
+use crate::services::entry_filter::{FilterContext, entry_filters};
+use crate::services::metadata_screen::screen_metadata;
+use crate::transaction::venue_buy;
+use crate::tx::TipProfile;
 use dashmap::DashMap;
+use futures::future::join_all;
 use futures::stream::{FuturesUnordered, StreamExt};
 use lazy_static::lazy_static;
 use once_cell::sync::Lazy;
 use solana_entry::entry::Entry;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
 use spl_associated_token_account::get_associated_token_address;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::Instant;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 use tokio::time::Duration;
@@ -25,18 +34,31 @@ pub struct TokenState {
     pub first_buy_price: Option<f64>,
     pub current_price: Option<f64>,
     pub balance: Option<u64>,
-    pub bonding_curve: Option<String>, // 可选: 用于判断是否刚发行
+    pub bonding_curve: Option<Pubkey>, // 可选: 用于判断是否刚发行
     pub sell_stage: u8,                // 0 = 未卖，1 = 卖过一阶段，2 = 卖过两阶段
     pub highest_price: f64,            // 👈 新增字段
     pub last_tx_time: Option<Instant>,
     pub last_tx_price: Option<f64>,
     pub token_creator: Pubkey,
     pub first_buy_time: Option<Instant>,
+    pub entry_source: EntrySource, // 决定使用哪套退出策略
+    pub phase: PositionPhase,      // 只由持仓的 owner 任务推进
+    pub wallet: Option<Pubkey>,    // 持有该代币的钱包，卖出时用它签名
+    pub curve: Option<CurveState>, // 价格订阅得到的最新储备，卖出时按它计算最少到手 SOL
+    pub migrated: bool,            // bonding curve 已完成，卖出走 PumpSwap 或 Raydium
+    pub venue: VenueKind,          // 发射台，决定曲线解码和买卖指令
     // 可扩展字段: 是否卖出、狙击时间戳等
 }
 
 lazy_static! {
     pub static ref TOKEN_TABLE: DashMap<Pubkey, TokenState> = DashMap::new();
+    // bonding_curve -> mint，与 TOKEN_TABLE 同步，价格监听按账户直接查找
+    pub static ref CURVE_INDEX: DashMap<Pubkey, Pubkey> = DashMap::new();
+}
+
+// 交易账户中命中的持仓 mint
+pub fn mint_for_keys(keys: &[Pubkey]) -> Option<Pubkey> {
+    keys.iter().find_map(|key| CURVE_INDEX.get(key).map(|mint| *mint))
 }
 
 pub fn update_token_state<F>(mint: Pubkey, update_fn: F)
@@ -54,6 +76,12 @@ where
         last_tx_price: None,
         token_creator: Pubkey::default(),
         first_buy_time: None,
+        entry_source: EntrySource::default(),
+        phase: PositionPhase::default(),
+        wallet: None,
+        curve: None,
+        migrated: false,
+        venue: VenueKind::default(),
     });
 
     update_fn(&mut entry);
@@ -123,6 +151,18 @@ async fn read_whitelist(path: &str) {
     }
 }
 
+// 批次中每个 mint 的买入: (交易下标, 代币数量)
+// 调用前需已加载查找表，加载失败的 V0 交易解析不到，按尽力而为处理
+fn collect_batch_buys(transactions: &[VersionedTransaction]) -> HashMap<Pubkey, Vec<(usize, u64)>> {
+    let mut buys: HashMap<Pubkey, Vec<(usize, u64)>> = HashMap::new();
+    for (index, tx) in transactions.iter().enumerate() {
+        for (mint, amount) in VENUES.iter().flat_map(|venue| venue.parse_buys(tx)) {
+            buys.entry(mint).or_default().push((index, amount));
+        }
+    }
+    buys
+}
+
 impl TransactionProcessor {
     pub fn print_results(results: &TransactionResults) {
         // println!("Pump交易数量: {}", results.pump_transactions.len());
@@ -158,170 +198,182 @@ impl TransactionProcessor {
             all_transactions.extend_from_slice(&entry.transactions);
         }
 
+        // 先补齐本批次 V0 交易的查找表，否则批次买入统计会漏掉这些交易
+        join_all(all_transactions.iter().map(|tx| {
+            alt_cache::ensure_lookups_loaded(&tx.message, venue::is_target_program)
+        }))
+        .await;
+
+        // 同一批次里其他钱包对新币的买入会排在我们之前成交，报价时先计入
+        let batch_buys = Arc::new(collect_batch_buys(&all_transactions));
+
         let mut futures = FuturesUnordered::new();
 
-        for chunk in all_transactions.chunks(BATCH_SIZE) {
+        for (chunk_index, chunk) in all_transactions.chunks(BATCH_SIZE).enumerate() {
             let chunk = chunk.to_vec(); // Clone chunk to move into task
             let results = Arc::clone(&results);
+            let batch_buys = Arc::clone(&batch_buys);
 
             futures.push(tokio::spawn(async move {
                 let mut batch_results = Vec::with_capacity(chunk.len() / 20);
 
-                for tx in chunk {
-                    if let Some(pump_tx) = PumpParser::parse_transaction(&tx) {
-                        let has_create = pump_tx
-                            .instructions
-                            .iter()
-                            .any(|ix| matches!(ix.instruction_type, PumpInstructionType::Create));
-                        let has_buy = pump_tx
-                            .instructions
-                            .iter()
-                            .any(|ix| matches!(ix.instruction_type, PumpInstructionType::Buy));
-
-                        let blacklist = BLACKLIST.read().await;
-                        if blacklist.contains(pump_tx.creator.as_str()) || blacklist.contains("all")
-                        {
+                for (offset, tx) in chunk.into_iter().enumerate() {
+                    let tx_index = chunk_index * BATCH_SIZE + offset;
+
+                    // 持仓代币的开发者卖出，在执行前抢先退出
+                    dev_exit::check_shred_sells(&tx);
+
+                    // 跟单钱包的买入不要求同一笔交易里有 Create
+                    if unsafe { *COPY_TRADE_ENABLED } && copy_trade::has_watched_signer(&tx) {
+                        tokio::spawn(copy_trade::mirror_buys(tx.clone(), slot));
+                    }
+
+                    let mut pump_tx = match venue::detect_launch(&tx) {
+                        Ok(Some(pump_tx)) => pump_tx,
+                        Ok(None) => continue,
+                        Err(e) => {
+                            let signature = tx.signatures.first().copied().unwrap_or_default();
+                            println!("[⚠️解析失败] {}: {}", signature, e);
                             continue;
                         }
+                    };
+
+                    if let Ok(mint) = Pubkey::from_str(&pump_tx.mint)
+                        && let Some(buys) = batch_buys.get(&mint)
+                    {
+                        let before = pump_tx.my_token_amount;
+                        pump_tx.apply_batch_buys(
+                            buys.iter()
+                                .filter(|(index, _)| *index != tx_index)
+                                .map(|(_, amount)| *amount),
+                        );
+                        if pump_tx.my_token_amount != before {
+                            println!(
+                                "[报价] {} 同批次有其他买入, 买入数量 {} -> {}",
+                                mint, before, pump_tx.my_token_amount
+                            );
+                        }
+                    }
 
-                        unsafe {
-                            if *WHITELIST_ENABLED  {
-                                let whitelist = WHITELIST.read().await;
-                                if !whitelist.contains(pump_tx.creator.as_str()) {
-                                    continue;
-                                }
-                            }
+                    let filters = entry_filters();
+                    let ctx = FilterContext {
+                        pump_tx: &pump_tx,
+                        slot,
+                    };
+                    if !filters.check(&ctx).await {
+                        continue;
+                    }
+
+                    let Ok(mint) = Pubkey::from_str(&pump_tx.mint) else {
+                        continue;
+                    };
+                    let Ok(bonding_curve) = Pubkey::from_str(&pump_tx.bonding_curve) else {
+                        continue;
+                    };
+                    let Ok(associated_bonding_curve) =
+                        Pubkey::from_str(&pump_tx.associated_bonding_curve)
+                    else {
+                        continue;
+                    };
+                    let Ok(creator) = Pubkey::from_str(&pump_tx.creator) else {
+                        continue;
+                    };
+
+                    // 元数据筛查与买入并行，不增加买入延迟
+                    let screening = unsafe { *METADATA_SCREEN_ENABLED }
+                        .then(|| tokio::spawn(screen_metadata(pump_tx.uri.clone())));
+
+                    let wallet = match risk::try_reserve_buy(
+                        mint,
+                        creator,
+                        EntrySource::DevSnipe,
+                        unsafe { *MAX_SOL },
+                    ) {
+                        Ok(wallet) => wallet,
+                        Err(reason) => {
+                            println!("[风控] 跳过 {}: {}", mint, reason);
+                            continue;
                         }
+                    };
+                    if !position::reserve(
+                        mint,
+                        bonding_curve,
+                        creator,
+                        wallet.pubkey(),
+                        EntrySource::DevSnipe,
+                        pump_tx.venue,
+                    ) {
+                        risk::release_buy(&mint);
+                        println!("[持仓] 跳过 {}: 已有持仓或买入中", mint);
+                        continue;
+                    }
 
-                        if has_create
-                            && has_buy
-                            && (300_000_000..=7_000_000_000).contains(&pump_tx.max_sol_cost)
-                        {
-                            let Ok(mint) = Pubkey::from_str(&pump_tx.mint) else {
-                                continue;
-                            };
-                            let Ok(bonding_curve) = Pubkey::from_str(&pump_tx.bonding_curve) else {
-                                continue;
-                            };
-                            let Ok(associated_bonding_curve) =
-                                Pubkey::from_str(&pump_tx.associated_bonding_curve)
-                            else {
-                                continue;
-                            };
-                            let Ok(creator) = Pubkey::from_str(&pump_tx.creator) else {
-                                continue;
-                            };
-
-                            // 并行执行 buy 和 sell
-                            let buy_result = pump_buy(
+                    // 并行执行 buy 和 sell
+                    let max_sol_cost = unsafe { (*MAX_SOL * 1_000_000_000.0) as u64 };
+                    let launch_buy = LaunchBuy {
+                        mint,
+                        bonding_curve,
+                        associated_bonding_curve,
+                        creator,
+                        create_slot: slot,
+                        token_amount: pump_tx.my_token_amount,
+                        max_sol_cost,
+                    };
+                    if let Err(e) =
+                        venue_buy(&wallet, venue::venue(pump_tx.venue), &launch_buy).await
+                    {
+                        println!("[❌买入失败] {}: {:?}", mint, e);
+                    }
+
+                    tokio::time::sleep(Duration::from_millis(1500)).await;
+                    let ata = get_associated_token_address(&wallet.pubkey(), &mint);
+                    let app_state = APP_STATE.get().expect("AppState not initialized");
+                    let rpc_client = &app_state.client;
+
+                    if let Ok(account) =
+                        get_account_info_fast(rpc_client, &mint, &ata).await
+                    {
+                        let balance = account.base.amount;
+
+                        if balance > 0 {
+                            println!(
+                                "🎯狙击成功: {}, 当前余额为: {}, 购买成本价: {},",
+                                mint, balance, pump_tx.price
+                            );
+                            // update_price_once(mint.clone().to_string(), pump_tx.price).await;
+                            // insert_address(bonding_curve.to_string()).await;
+                            open_position(
                                 mint,
                                 bonding_curve,
-                                associated_bonding_curve,
                                 creator,
-                                slot,
+                                wallet.pubkey(),
                                 pump_tx.price,
-                                pump_tx.my_token_amount,
-                            )
-                                .await;
-
-                            tokio::time::sleep(Duration::from_millis(1500)).await;
-                            let wallet_pubkey =
-                                Pubkey::from_str(PUBLIC_KEY.as_str()).unwrap();
-
-                            let ata = get_associated_token_address(&wallet_pubkey, &mint);
-                            let app_state = APP_STATE.get().expect("AppState not initialized");
-                            let rpc_client = &app_state.client;
-
-                            if let Ok(account) =
-                                get_account_info_fast(&rpc_client, &mint, &ata).await
+                                balance,
+                                EntrySource::DevSnipe,
+                                pump_tx.venue,
+                            );
+                            dev_exit::link_dev_wallets(mint, pump_tx.buyers());
+
+                            // 元数据筛查不通过，立即卖出
+                            if let Some(screening) = screening
+                                && let Ok(Err(reason)) = screening.await
                             {
-                                let balance = account.base.amount;
-
-                                if balance > 0 {
-                                    println!(
-                                        "{}: {}, {}: {}, {}: {},",
-                                        "🎯狙击成功",
-                                        mint,
-                                        "当前余额为",
-                                        balance.to_string(),
-                                        "购买成本价",
-                                        pump_tx.price
-                                    );
-                                    // update_price_once(mint.clone().to_string(), pump_tx.price).await;
-                                    // insert_address(bonding_curve.to_string()).await;
-                                    update_token_state(mint.clone(), |state| {
-                                        if state.first_buy_price.is_none() {
-                                            state.first_buy_price = Some(pump_tx.price);
-                                        }
-                                        if state.current_price.is_none() {
-                                            state.current_price = Some(pump_tx.price);
-                                        }
-                                        if state.balance.is_none() {
-                                            state.balance = Some(balance);
-                                        }
-                                        if state.bonding_curve.is_none() {
-                                            state.bonding_curve = Some(bonding_curve.to_string());
-                                        }
-                                        let now = Instant::now();
-                                        if state.last_tx_time.is_none() {
-                                            state.last_tx_time = Some(now);
-                                        }
-                                        if state.last_tx_price.is_none() {
-                                            state.last_tx_price = Some(pump_tx.price);
-                                        }
-
-                                        state.token_creator = creator;
-
-                                        if state.first_buy_time.is_none() {
-                                            state.first_buy_time = Some(now);
-                                        }
-                                    });
-
-                                    // ✅ 在这之后启动4秒止损监测任务
-                                    let mint_clone = mint.clone();
-                                    tokio::spawn(async move {
-                                        tokio::time::sleep(Duration::from_millis(2000)).await;
-
-                                        println!("[🔻开始判断3.5秒止损] {}, [{:?}]", mint_clone, SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
-                                        if let Some(state) = TOKEN_TABLE.get(&mint_clone) {
-                                            let first = state.first_buy_price.unwrap_or(0.0);
-                                            let current = state.current_price.unwrap_or(0.0);
-                                            let creator_pubkey = creator.clone();
-                                            drop(state); // ✅ 显式释放锁，避免与 remove 冲突
-
-                                            let change = ((current - first) / first).abs();
-
-                                            if change < 0.20 {
-                                                println!("[🔻3.5秒止损触发] {} 当前价: {:.12}, 原价: {:.12}, 变动: {:.2}%, [{:?}]", mint_clone, current, first, change * 100.0,  SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
-
-                                                if let Err(e) = pump_sell(
-                                                    mint_clone.clone(),
-                                                    creator_pubkey,
-                                                    balance,
-                                                )
-                                                    .await
-                                                {
-                                                    println!("[❌止损失败] {:?}", e);
-                                                } else {
-                                                    TOKEN_TABLE.remove(&mint_clone); // ✅ 现在不会死锁
-                                                    println!(
-                                                        "[✅止损成功] 已卖出代币 {}, [{:?}]",
-                                                        mint_clone, SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
-                                                    );
-                                                }
-                                            } else {
-                                                println!("[✅无需止损] {} 价格已涨 {:.2}%，未触发3.5秒止损。", mint_clone, change * 100.0);
-                                            }
-                                        }
-                                    });
-                                } else {
-                                    println!("查询ATA失败（可能不存在或错误），不卖出。");
-                                }
+                                println!("[🚫元数据筛查未通过] {}: {}", mint, reason);
+                                position::request_exit(
+                                    mint,
+                                    format!("元数据筛查未通过: {}", reason),
+                                    TipProfile::normal(),
+                                );
                             }
-
-                            batch_results.push(pump_tx);
+                        } else {
+                            position::abandon(mint);
                         }
+                    } else {
+                        println!("查询ATA失败（可能不存在或错误），不卖出。");
+                        position::abandon(mint);
                     }
+
+                    batch_results.push(pump_tx);
                 }
 
                 if !batch_results.is_empty() {
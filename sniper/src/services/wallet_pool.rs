@@ -0,0 +1,310 @@
+use crate::config::keystore::{keypair_from_base58, load_keypair_file, load_keystore};
+use crate::config::{KEYPAIR_PATH, KEYSTORE_PATH, NONCE_PUBKEY, PRIVATE_KEY, PUBLIC_KEY};
+use crate::models::exit_strategy::EntrySource;
+use crate::services::sol_balance;
+use anyhow::{Result, anyhow};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::RwLock;
+
+// 钱包池配置，不存在时使用 .env 中配置的单个钱包
+pub const WALLETS_PATH: &str = "wallets.json";
+
+// 买入时选择钱包的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WalletPolicy {
+    #[default]
+    RoundRobin,
+    // 资金占用最少的钱包
+    LeastExposed,
+    // 按入场来源使用专属钱包，没有专属钱包时轮流使用未分配的钱包
+    Dedicated,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletConfig {
+    #[serde(default)]
+    pub name: String,
+    // 私钥来源三选一，优先 keystore
+    #[serde(default)]
+    pub keystore: Option<String>,
+    #[serde(default)]
+    pub keypair_path: Option<String>,
+    #[serde(default)]
+    pub private_key: Option<String>,
+    // 可选的钱包地址，启动自检时与私钥推导出的地址核对
+    #[serde(default)]
+    pub pubkey: Option<String>,
+    // 每个钱包使用自己的 nonce 账户，互不影响
+    pub nonce: String,
+    #[serde(default)]
+    pub dedicated: Vec<EntrySource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletPoolConfig {
+    #[serde(default)]
+    pub policy: WalletPolicy,
+    pub wallets: Vec<WalletConfig>,
+}
+
+pub struct Wallet {
+    pub name: String,
+    pub keypair: Arc<Keypair>,
+    pub nonce: Pubkey,
+    pub dedicated: Vec<EntrySource>,
+    pub declared_pubkey: Option<String>,
+    // nonce 账户当前的 blockhash，买入后更新
+    pub blockhash: RwLock<Hash>,
+}
+
+impl Wallet {
+    fn from_config(config: &WalletConfig) -> Result<Self> {
+        let keypair = if let Some(path) = &config.keystore {
+            load_keystore(path)
+        } else if let Some(path) = &config.keypair_path {
+            load_keypair_file(path)
+        } else if let Some(private_key) = &config.private_key {
+            keypair_from_base58(private_key)
+        } else {
+            Err(anyhow!("没有配置私钥"))
+        }
+        .map_err(|e| anyhow!("钱包 {} 私钥无效: {}", config.name, e))?;
+        let nonce = Pubkey::from_str(&config.nonce)
+            .map_err(|e| anyhow!("钱包 {} nonce 地址无效: {}", config.name, e))?;
+        let name = if config.name.is_empty() {
+            keypair.pubkey().to_string()
+        } else {
+            config.name.clone()
+        };
+
+        Ok(Self {
+            name,
+            keypair: Arc::new(keypair),
+            nonce,
+            dedicated: config.dedicated.clone(),
+            declared_pubkey: config.pubkey.clone(),
+            blockhash: RwLock::new(Hash::default()),
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+}
+
+impl std::fmt::Debug for Wallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Wallet")
+            .field("name", &self.name)
+            .field("pubkey", &self.pubkey())
+            .field("nonce", &self.nonce)
+            .finish()
+    }
+}
+
+struct WalletPool {
+    policy: WalletPolicy,
+    wallets: Vec<Arc<Wallet>>,
+    // 轮询计数
+    next: AtomicUsize,
+}
+
+impl WalletPool {
+    fn load(path: &str) -> Result<Self> {
+        let config = match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str::<WalletPoolConfig>(&content)?,
+            Err(_) => WalletPoolConfig {
+                policy: WalletPolicy::RoundRobin,
+                wallets: vec![WalletConfig {
+                    name: "default".to_string(),
+                    keystore: KEYSTORE_PATH.clone(),
+                    keypair_path: KEYPAIR_PATH.clone(),
+                    private_key: PRIVATE_KEY.clone(),
+                    pubkey: PUBLIC_KEY.clone(),
+                    nonce: NONCE_PUBKEY.clone(),
+                    dedicated: vec![],
+                }],
+            },
+        };
+
+        if config.wallets.is_empty() {
+            return Err(anyhow!("{} 中没有钱包", path));
+        }
+
+        let wallets = config
+            .wallets
+            .iter()
+            .map(|wallet| Wallet::from_config(wallet).map(Arc::new))
+            .collect::<Result<Vec<_>>>()?;
+
+        println!(
+            "[钱包池] 加载 {} 个钱包, 选择方式: {:?}",
+            wallets.len(),
+            config.policy
+        );
+        Ok(Self {
+            policy: config.policy,
+            wallets,
+            next: AtomicUsize::new(0),
+        })
+    }
+}
+
+static WALLET_POOL: Lazy<WalletPool> =
+    Lazy::new(|| WalletPool::load(WALLETS_PATH).expect("加载钱包池失败"));
+
+// 启动时加载，keystore 需要在终端输入口令，之后不再解密
+pub fn load_wallet_pool() {
+    Lazy::force(&WALLET_POOL);
+}
+
+pub fn wallets() -> &'static [Arc<Wallet>] {
+    &WALLET_POOL.wallets
+}
+
+// 持仓记录的钱包，卖出时用它签名
+pub fn wallet(pubkey: &Pubkey) -> Option<Arc<Wallet>> {
+    WALLET_POOL
+        .wallets
+        .iter()
+        .find(|wallet| wallet.pubkey() == *pubkey)
+        .cloned()
+}
+
+// 按策略在候选钱包中选择，exposure 为每个钱包当前的资金占用 (SOL)
+fn pick<'a>(
+    policy: WalletPolicy,
+    candidates: &[&'a Arc<Wallet>],
+    source: EntrySource,
+    exposure: &HashMap<Pubkey, f64>,
+    counter: &AtomicUsize,
+) -> Option<&'a Arc<Wallet>> {
+    let candidates = match policy {
+        WalletPolicy::Dedicated => {
+            let dedicated = candidates
+                .iter()
+                .filter(|wallet| wallet.dedicated.contains(&source))
+                .copied()
+                .collect::<Vec<_>>();
+            if dedicated.is_empty() {
+                candidates
+                    .iter()
+                    .filter(|wallet| wallet.dedicated.is_empty())
+                    .copied()
+                    .collect()
+            } else {
+                dedicated
+            }
+        }
+        _ => candidates.to_vec(),
+    };
+
+    if candidates.is_empty() {
+        return None;
+    }
+
+    match policy {
+        WalletPolicy::LeastExposed => candidates.into_iter().min_by(|a, b| {
+            let a = exposure.get(&a.pubkey()).copied().unwrap_or(0.0);
+            let b = exposure.get(&b.pubkey()).copied().unwrap_or(0.0);
+            a.total_cmp(&b)
+        }),
+        _ => {
+            let index = counter.fetch_add(1, Ordering::Relaxed) % candidates.len();
+            Some(candidates[index])
+        }
+    }
+}
+
+// 选出本次买入使用的钱包，余额不足的钱包不参与
+pub fn select_wallet(
+    source: EntrySource,
+    sol_amount: f64,
+    exposure: &HashMap<Pubkey, f64>,
+) -> Result<Arc<Wallet>, String> {
+    let mut last_rejection = None;
+    let affordable = WALLET_POOL
+        .wallets
+        .iter()
+        .filter(
+            |wallet| match sol_balance::check_affordable(&wallet.pubkey(), sol_amount) {
+                Ok(()) => true,
+                Err(reason) => {
+                    last_rejection = Some(reason);
+                    false
+                }
+            },
+        )
+        .collect::<Vec<_>>();
+
+    if affordable.is_empty() {
+        return Err(last_rejection.unwrap_or_else(|| "没有可用钱包".to_string()));
+    }
+
+    pick(
+        WALLET_POOL.policy,
+        &affordable,
+        source,
+        exposure,
+        &WALLET_POOL.next,
+    )
+    .cloned()
+    .ok_or_else(|| format!("没有分配给 {:?} 的可用钱包", source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallet(dedicated: Vec<EntrySource>) -> Arc<Wallet> {
+        Arc::new(Wallet {
+            name: String::new(),
+            keypair: Arc::new(Keypair::new()),
+            nonce: Pubkey::new_unique(),
+            dedicated,
+            declared_pubkey: None,
+            blockhash: RwLock::new(Hash::default()),
+        })
+    }
+
+    #[test]
+    fn test_pick_policies() {
+        let wallets = [
+            wallet(vec![]),
+            wallet(vec![]),
+            wallet(vec![EntrySource::CopyTrade]),
+        ];
+        let candidates = wallets.iter().collect::<Vec<_>>();
+        let counter = AtomicUsize::new(0);
+        let mut exposure = HashMap::new();
+
+        // 轮询
+        let first = pick(WalletPolicy::RoundRobin, &candidates, EntrySource::DevSnipe, &exposure, &counter);
+        let second = pick(WalletPolicy::RoundRobin, &candidates, EntrySource::DevSnipe, &exposure, &counter);
+        assert_ne!(first.unwrap().pubkey(), second.unwrap().pubkey());
+
+        // 资金占用最少
+        exposure.insert(wallets[0].pubkey(), 0.2);
+        exposure.insert(wallets[1].pubkey(), 0.1);
+        exposure.insert(wallets[2].pubkey(), 0.3);
+        let least = pick(WalletPolicy::LeastExposed, &candidates, EntrySource::DevSnipe, &exposure, &counter);
+        assert_eq!(least.unwrap().pubkey(), wallets[1].pubkey());
+
+        // 专属钱包，没有专属时只用未分配的钱包
+        for _ in 0..3 {
+            let copy = pick(WalletPolicy::Dedicated, &candidates, EntrySource::CopyTrade, &exposure, &counter);
+            assert_eq!(copy.unwrap().pubkey(), wallets[2].pubkey());
+            let snipe = pick(WalletPolicy::Dedicated, &candidates, EntrySource::DevSnipe, &exposure, &counter);
+            assert_ne!(snipe.unwrap().pubkey(), wallets[2].pubkey());
+        }
+    }
+}
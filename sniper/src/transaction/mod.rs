@@ -1,8 +1,28 @@
 use crate::api::APP_STATE;
-use crate::config::{MAX_SOL, NONCE_PUBKEY, PRIVATE_KEY};
-use crate::tx::{tx_pump_buy, tx_pump_sell, update_nonce};
+use crate::services::pump_global::fee_recipient;
+use crate::services::wallet_pool::Wallet;
+use crate::models::bonding_curve::FEE_DENOMINATOR;
+use crate::models::venue::{CurveSell, LaunchBuy, Venue};
+use crate::models::boop_parser::{
+    BOOP_BUY_TOKEN_DISCRIMINATOR, BOOP_PROGRAM_ID, BOOP_SELL_TOKEN_DISCRIMINATOR, BoopAccounts,
+};
+use crate::models::pump_amm::{
+    AmmGlobalConfig, PUMP_AMM_EVENT_AUTHORITY, PUMP_AMM_GLOBAL_CONFIG, PUMP_AMM_PROGRAM_ID,
+    PoolAccount, PoolState, canonical_pool, coin_creator_vault_authority,
+};
+use crate::models::raydium_amm::{
+    AMM_INFO_LEN, AMM_STATUS_SWAP, AmmInfo, BASE_MINT_OFFSET, QUOTE_MINT_OFFSET,
+};
+use crate::tx::{TipProfile, tx_pump_amm_sell, tx_pump_buy, tx_pump_sell, update_nonce};
 use anyhow::{anyhow, Error, Result}; // 引入 anyhow
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::program_pack::Pack;
 use solana_sdk::signature::read_keypair_file;
 use solana_sdk::signature::Signer; // 导入 Signer trait
 use solana_sdk::signer::keypair::Keypair;
@@ -30,15 +50,16 @@ use tokio::{fs, time};
 pub const GLOBAL_ACCOUNT: Pubkey =
     solana_sdk::pubkey!("4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf");
 
+// Global 账户未加载时的默认收费地址，实际使用 pump_global::fee_recipient()
 pub const FEE_RECIPIENT: Pubkey =
     solana_sdk::pubkey!("62qc2CNXwrYqQScmEdiZFFAnJR262PxWEuNQtxfafNgV");
 const EVENT_AUTHORITY: Pubkey = solana_sdk::pubkey!("Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1");
 pub const PUMP_PROGRAM_ID: Pubkey =
     solana_sdk::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
 
-const PROXY_PROGRAM: Pubkey = solana_sdk::pubkey!("7uVmFk3SYJEgvD9unVPKzS19gSAg5b6CYzMP4er1HeKQ");
+pub const PROXY_PROGRAM: Pubkey = solana_sdk::pubkey!("7uVmFk3SYJEgvD9unVPKzS19gSAg5b6CYzMP4er1HeKQ");
 
-const RAYDIUM_PROGRAM_ID: Pubkey =
+pub const RAYDIUM_PROGRAM_ID: Pubkey =
     solana_sdk::pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
 const AMM_AUTHORITY: Pubkey = solana_sdk::pubkey!("5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1");
 const WSOL: Pubkey = solana_sdk::pubkey!("So11111111111111111111111111111111111111112");
@@ -71,35 +92,39 @@ fn create_ata_token_account_instr(
     associated_token_account_idempotent
 }
 
-pub async fn pump_buy(
-    token_mint: Pubkey,
-    bonding_curve: Pubkey,
-    assoc_bonding_curve: Pubkey,
-    creator_account: Pubkey,
-    create_slot: u64,
-    price: f64,
-    token_amount: u64,
-) -> Result<(), Error> {
+// 按发射台构建买入指令并发送，发送后推进钱包的 nonce
+pub async fn venue_buy(wallet: &Wallet, venue: &dyn Venue, buy: &LaunchBuy) -> Result<(), Error> {
     let start_build = Instant::now();
-    let (creator_vault, _) = Pubkey::find_program_address(
-        &[b"creator-vault", creator_account.as_ref()],
-        &PUMP_PROGRAM_ID,
+    let instructions = venue.build_buy(&wallet.pubkey(), buy);
+    let build_duration = start_build.elapsed();
+    println!(
+        "{:?} buy 本地构建花费 {:?}, mint: {:?}, [{}]",
+        venue.kind(),
+        build_duration,
+        buy.mint,
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
     );
-    // println!("开始狙击代币: {}", token_mint);
-    let signer = solana_sdk::signature::Keypair::from_base58_string(PRIVATE_KEY.as_str());
 
-    let mut max_sol_cost  = 0 ;
-    unsafe {
-        max_sol_cost = (*MAX_SOL * 1_000_000_000.0) as u64; // lamports 0.15 sol
+    #[cfg(not(test))]
+    tx_pump_buy(wallet, instructions).await?;
+    // let snipe_duration = start_build.elapsed();
+    // println!("狙击完成总耗时 {:?}", snipe_duration);
+    let app_state = APP_STATE.get().expect("AppState not initialized");
+
+    if let Err(e) = update_nonce(app_state, wallet).await {
+        println!("[钱包池] 钱包 {} 更新 nonce 失败: {:?}", wallet.name, e);
     }
-    let token_price: f64 = price; // 单位是 SOL/个
 
-    // println!("投入: {} SOL", max_sol);
-    // println!("实际价格: {} SOL/token", token_price);
-    // println!("尝试购买: {} 代币(含精度)", token_amount);
+    Ok(())
+}
+
+// PUMP 买入: [过期 slot 检查, 创建 ATA, 经代理程序买入]
+pub fn pump_buy_instructions(buyer: &Pubkey, buy: &LaunchBuy) -> Vec<Instruction> {
+    let (creator_vault, _) =
+        Pubkey::find_program_address(&[b"creator-vault", buy.creator.as_ref()], &PUMP_PROGRAM_ID);
 
     // 指令1：过期 slot 检查
-    let expiry_slot: u64 = create_slot + 1;
+    let expiry_slot: u64 = buy.create_slot + 1;
     let min_balance: u64 = 0; // 0.982 SOL
 
     // 构建过期检查的数据
@@ -117,38 +142,32 @@ pub async fn pump_buy(
         &expired_data,
         vec![
             // 传入多个账户（可以是混淆账户）
-            AccountMeta::new_readonly(assoc_bonding_curve, false),
-            AccountMeta::new_readonly(bonding_curve, false),
-            AccountMeta::new_readonly(creator_account, false),
+            AccountMeta::new_readonly(buy.associated_bonding_curve, false),
+            AccountMeta::new_readonly(buy.bonding_curve, false),
+            AccountMeta::new_readonly(buy.creator, false),
         ],
     );
 
     let mut data = Vec::with_capacity(24);
     data.extend_from_slice(PUMP_SELECTOR); // 添加 pump 选择器
-    data.extend_from_slice(&token_amount.to_le_bytes()); // 添加 token 数量
-    data.extend_from_slice(&max_sol_cost.to_le_bytes()); // 添加最大 SOL 费用
-
-    let token_mint = token_mint;
-
-    let bonding_curve_address = bonding_curve;
+    data.extend_from_slice(&buy.token_amount.to_le_bytes()); // 添加 token 数量
+    data.extend_from_slice(&buy.max_sol_cost.to_le_bytes()); // 添加最大 SOL 费用
 
-    let associated_user = get_associated_token_address(&signer.pubkey(), &token_mint);
+    let associated_user = get_associated_token_address(buyer, &buy.mint);
 
-    let associated_bonding_curve = assoc_bonding_curve;
-
-    let create_ata = create_ata_token_account_instr(spl_token::id(), &token_mint, &signer.pubkey());
+    let create_ata = create_ata_token_account_instr(spl_token::id(), &buy.mint, buyer);
 
     let pump_instruction = Instruction::new_with_bytes(
         PROXY_PROGRAM,
         &data,
         vec![
             AccountMeta::new_readonly(GLOBAL_ACCOUNT, false),
-            AccountMeta::new(FEE_RECIPIENT, false),
-            AccountMeta::new_readonly(token_mint, false),
-            AccountMeta::new(bonding_curve_address, false),
-            AccountMeta::new(associated_bonding_curve, false),
+            AccountMeta::new(fee_recipient(), false),
+            AccountMeta::new_readonly(buy.mint, false),
+            AccountMeta::new(buy.bonding_curve, false),
+            AccountMeta::new(buy.associated_bonding_curve, false),
             AccountMeta::new(associated_user, false),
-            AccountMeta::new(signer.pubkey(), true),
+            AccountMeta::new(*buyer, true),
             AccountMeta::new_readonly(system_program::id(), false),
             AccountMeta::new_readonly(spl_token::id(), false),
             AccountMeta::new(creator_vault, false),
@@ -157,64 +176,65 @@ pub async fn pump_buy(
         ],
     );
 
-    let instructions = vec![expired_instruction, create_ata, pump_instruction];
-    let build_duration = start_build.elapsed();
-    println!("pumpbuy 本地构建花费 {:?}, mint: {:?}, [{}]", build_duration, token_mint, SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+    vec![expired_instruction, create_ata, pump_instruction]
+}
 
-    #[cfg(not(test))]
-    let sig = tx_pump_buy(&signer, instructions).await?;
-    // let snipe_duration = start_build.elapsed();
-    // println!("狙击完成总耗时 {:?}", snipe_duration);
-    let app_state = APP_STATE.get().expect("AppState not initialized");
-    let nonce_pubkey2 = Pubkey::from_str(NONCE_PUBKEY.as_ref()).unwrap();
-    let nonce_pubkey = Arc::new(nonce_pubkey2);
+// 按发射台构建 bonding curve 卖出指令并发送
+pub async fn venue_sell_with_tip(
+    wallet: &Wallet,
+    venue: &dyn Venue,
+    sell: &CurveSell,
+    tip: TipProfile,
+) -> Result<(), Error> {
+    println!("开始出售代币");
+    let signer = wallet.keypair.as_ref();
 
-    update_nonce(&app_state, nonce_pubkey).await;
+    println!("出售代币: {} ({:?})", sell.mint, venue.kind());
+    println!(
+        "代币数量: {:.2}, 最少到手: {} lamports",
+        sell.token_amount, sell.min_sol_output
+    );
+
+    let instructions = venue.build_sell(&signer.pubkey(), sell);
+
+    #[cfg(not(test))]
+    tx_pump_sell(signer, instructions, tip).await?;
 
     Ok(())
 }
 
-pub async fn pump_sell(
-    token_mint: Pubkey,
-    creator_account: Pubkey,
-    token_amount: u64,
-) -> Result<(), Error> {
-    // let start_build = Instant::now();
-    println!("开始出售代币");
-    let signer = solana_sdk::signature::Keypair::from_base58_string(PRIVATE_KEY.as_str());
-
+// PUMP 卖出: 经代理程序卖出，bonding curve 按 mint 派生
+pub fn pump_sell_instructions(seller: &Pubkey, sell: &CurveSell) -> Vec<Instruction> {
     let (creator_vault, _) = Pubkey::find_program_address(
-        &[b"creator-vault", creator_account.as_ref()],
+        &[b"creator-vault", sell.creator.as_ref()],
         &PUMP_PROGRAM_ID,
     );
 
-    println!("出售代币: {} ", token_mint);
-    println!("代币数量: {:.2} ", token_amount);
-
-    let (bonding_curve_address, associated_bonding_curve) =
-        get_bonding_curve_account(&token_mint, &PUMP_PROGRAM_ID).await?;
-
-    let min_sol_receive: u64 = 0;
+    let (bonding_curve_address, _) = Pubkey::find_program_address(
+        &[BONDING_CURVE_SEED, sell.mint.as_ref()],
+        &PUMP_PROGRAM_ID,
+    );
+    let associated_bonding_curve = get_associated_token_address(&bonding_curve_address, &sell.mint);
 
     let mut data = Vec::with_capacity(24);
     data.extend_from_slice(PUMP_SELL_SELECTOR);
-    data.extend_from_slice(&token_amount.to_le_bytes());
-    data.extend_from_slice(&min_sol_receive.to_le_bytes());
+    data.extend_from_slice(&sell.token_amount.to_le_bytes());
+    data.extend_from_slice(&sell.min_sol_output.to_le_bytes());
 
     // 用户代币关联账户
-    let associated_user = get_associated_token_address(&signer.pubkey(), &token_mint);
+    let associated_user = get_associated_token_address(seller, &sell.mint);
 
     let pump_instruction = Instruction::new_with_bytes(
         PROXY_PROGRAM,
         &data,
         vec![
             AccountMeta::new_readonly(GLOBAL_ACCOUNT, false),
-            AccountMeta::new(FEE_RECIPIENT, false),
-            AccountMeta::new_readonly(token_mint, false),
+            AccountMeta::new(fee_recipient(), false),
+            AccountMeta::new_readonly(sell.mint, false),
             AccountMeta::new(bonding_curve_address, false),
             AccountMeta::new(associated_bonding_curve, false),
             AccountMeta::new(associated_user, false),
-            AccountMeta::new(signer.pubkey(), true),
+            AccountMeta::new(*seller, true),
             AccountMeta::new_readonly(system_program::id(), false),
             AccountMeta::new(creator_vault, false),
             AccountMeta::new_readonly(spl_token::id(), false),
@@ -224,16 +244,436 @@ pub async fn pump_sell(
         ],
     );
 
-    let instructions = vec![pump_instruction];
-    // let build_duration = start_build.elapsed();
-    // println!("pumpsell build took {:?}", build_duration);
+    vec![pump_instruction]
+}
+
+// Boop 买入: [创建 ATA, buy_token]，直接调用 Boop 程序
+// buy_token(buy_amount, amount_out_min): 投入 max_sol_cost，最少得到 token_amount
+pub fn boop_buy_instructions(buyer: &Pubkey, buy: &LaunchBuy) -> Vec<Instruction> {
+    let accounts = BoopAccounts::derive(&buy.mint);
+    let associated_user = get_associated_token_address(buyer, &buy.mint);
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&BOOP_BUY_TOKEN_DISCRIMINATOR);
+    data.extend_from_slice(&buy.max_sol_cost.to_le_bytes());
+    data.extend_from_slice(&buy.token_amount.to_le_bytes());
+
+    let create_ata = create_ata_token_account_instr(spl_token::id(), &buy.mint, buyer);
+
+    let boop_instruction = Instruction::new_with_bytes(
+        BOOP_PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new_readonly(buy.mint, false),
+            AccountMeta::new(accounts.bonding_curve, false),
+            AccountMeta::new(accounts.trading_fees_vault, false),
+            AccountMeta::new(accounts.bonding_curve_vault, false),
+            AccountMeta::new(accounts.bonding_curve_sol_vault, false),
+            AccountMeta::new(associated_user, false),
+            AccountMeta::new(*buyer, true),
+            AccountMeta::new_readonly(accounts.config, false),
+            AccountMeta::new_readonly(accounts.vault_authority, false),
+            AccountMeta::new_readonly(spl_token::native_mint::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ],
+    );
+
+    vec![create_ata, boop_instruction]
+}
+
+// Boop 卖出: sell_token，SOL 直接回到卖出钱包
+pub fn boop_sell_instructions(seller: &Pubkey, sell: &CurveSell) -> Vec<Instruction> {
+    let accounts = BoopAccounts::derive(&sell.mint);
+    let associated_user = get_associated_token_address(seller, &sell.mint);
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&BOOP_SELL_TOKEN_DISCRIMINATOR);
+    data.extend_from_slice(&sell.token_amount.to_le_bytes());
+    data.extend_from_slice(&sell.min_sol_output.to_le_bytes());
+
+    let boop_instruction = Instruction::new_with_bytes(
+        BOOP_PROGRAM_ID,
+        &data,
+        vec![
+            AccountMeta::new_readonly(sell.mint, false),
+            AccountMeta::new(accounts.bonding_curve, false),
+            AccountMeta::new(accounts.trading_fees_vault, false),
+            AccountMeta::new(accounts.bonding_curve_vault, false),
+            AccountMeta::new(accounts.bonding_curve_sol_vault, false),
+            AccountMeta::new(associated_user, false),
+            AccountMeta::new(*seller, true),
+            AccountMeta::new(*seller, false),
+            AccountMeta::new_readonly(accounts.config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ],
+    );
+
+    vec![boop_instruction]
+}
+
+// 迁移后的 PumpSwap 池子，卖出前按最新储备报价
+#[derive(Debug, Clone)]
+pub struct PumpAmmPool {
+    pub address: Pubkey,
+    pub pool: PoolAccount,
+    pub protocol_fee_recipient: Pubkey,
+    pub state: PoolState,
+}
+
+// 按迁移池子的地址查找，池子还没创建时返回错误
+pub async fn find_pump_amm_pool(token_mint: &Pubkey) -> Result<PumpAmmPool> {
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+    let client = &app_state.client;
+
+    let address = canonical_pool(token_mint);
+    let accounts = client
+        .get_multiple_accounts_with_commitment(
+            &[address, PUMP_AMM_GLOBAL_CONFIG],
+            CommitmentConfig::processed(),
+        )
+        .await?
+        .value;
+    let pool = match &accounts[0] {
+        Some(account) => PoolAccount::decode(&account.data)?,
+        None => return Err(anyhow!("{} 的 PumpSwap 池子尚未创建", token_mint)),
+    };
+    if pool.base_mint != *token_mint || pool.quote_mint != spl_token::native_mint::id() {
+        return Err(anyhow!("池子 {} 不是 {}/WSOL", address, token_mint));
+    }
+    let config = accounts[1]
+        .as_ref()
+        .ok_or_else(|| anyhow!("PumpSwap GlobalConfig 不存在"))
+        .and_then(|account| AmmGlobalConfig::decode(&account.data))?;
+    let protocol_fee_recipient = config
+        .protocol_fee_recipient()
+        .ok_or_else(|| anyhow!("PumpSwap 没有协议收费地址"))?;
+
+    let reserves = client
+        .get_multiple_accounts_with_commitment(
+            &[pool.pool_base_token_account, pool.pool_quote_token_account],
+            CommitmentConfig::processed(),
+        )
+        .await?
+        .value
+        .iter()
+        .map(|account| {
+            account
+                .as_ref()
+                .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+                .map(|token_account| token_account.amount)
+                .ok_or_else(|| anyhow!("池子 {} 的代币账户无效", address))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let state = PoolState {
+        base_reserves: reserves[0],
+        quote_reserves: reserves[1],
+        lp_fee_basis_points: config.lp_fee_basis_points,
+        protocol_fee_basis_points: config.protocol_fee_basis_points,
+        coin_creator_fee_basis_points: if pool.coin_creator == Pubkey::default() {
+            0
+        } else {
+            config.coin_creator_fee_basis_points
+        },
+    };
+
+    Ok(PumpAmmPool {
+        address,
+        pool,
+        protocol_fee_recipient,
+        state,
+    })
+}
+
+// 通过 PumpSwap 卖出，卖得的 WSOL 在同一笔交易里关闭账户换回 SOL
+pub async fn pump_amm_sell_with_tip(
+    wallet: &Wallet,
+    pool: &PumpAmmPool,
+    token_amount: u64,
+    // 最少到手 SOL (lamports)，由调用方按池子储备报价
+    min_sol_output: u64,
+    tip: TipProfile,
+) -> Result<(), Error> {
+    let signer = wallet.keypair.as_ref();
+
+    println!("PumpSwap 出售代币: {}, 池子: {}", pool.pool.base_mint, pool.address);
+    println!("代币数量: {:.2}, 最少到手: {} lamports", token_amount, min_sol_output);
+
+    let instructions =
+        pump_amm_sell_instructions(&signer.pubkey(), pool, token_amount, min_sol_output)?;
+
+    #[cfg(not(test))]
+    tx_pump_amm_sell(signer, instructions, tip).await?;
+
+    Ok(())
+}
+
+// PumpSwap 卖出指令：建 WSOL 账户、卖出、关闭 WSOL 账户
+pub fn pump_amm_sell_instructions(
+    seller: &Pubkey,
+    pool: &PumpAmmPool,
+    token_amount: u64,
+    min_sol_output: u64,
+) -> Result<Vec<Instruction>> {
+    let token_mint = pool.pool.base_mint;
+
+    let user_base_account = get_associated_token_address(seller, &token_mint);
+    let user_quote_account = get_associated_token_address(seller, &WSOL);
+    let coin_creator_vault_authority = coin_creator_vault_authority(&pool.pool.coin_creator);
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(PUMP_AMM_SELL_SELECTOR);
+    data.extend_from_slice(&token_amount.to_le_bytes());
+    data.extend_from_slice(&min_sol_output.to_le_bytes());
+
+    // 账户顺序同 PumpSwap sell，末尾两个为创作者费金库
+    let sell_instruction = Instruction::new_with_bytes(
+        PROXY_PROGRAM,
+        &data,
+        vec![
+            AccountMeta::new(pool.address, false),
+            AccountMeta::new(*seller, true),
+            AccountMeta::new_readonly(PUMP_AMM_GLOBAL_CONFIG, false),
+            AccountMeta::new_readonly(token_mint, false),
+            AccountMeta::new_readonly(WSOL, false),
+            AccountMeta::new(user_base_account, false),
+            AccountMeta::new(user_quote_account, false),
+            AccountMeta::new(pool.pool.pool_base_token_account, false),
+            AccountMeta::new(pool.pool.pool_quote_token_account, false),
+            AccountMeta::new_readonly(pool.protocol_fee_recipient, false),
+            AccountMeta::new(
+                get_associated_token_address(&pool.protocol_fee_recipient, &WSOL),
+                false,
+            ),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(PUMP_AMM_EVENT_AUTHORITY, false),
+            AccountMeta::new_readonly(PUMP_AMM_PROGRAM_ID, false),
+            AccountMeta::new(
+                get_associated_token_address(&coin_creator_vault_authority, &WSOL),
+                false,
+            ),
+            AccountMeta::new_readonly(coin_creator_vault_authority, false),
+        ],
+    );
+
+    let (open_wsol, close_wsol) = wsol_account_instructions(seller, 0)?;
+    let mut instructions = open_wsol;
+    instructions.push(sell_instruction);
+    instructions.push(close_wsol);
+    Ok(instructions)
+}
+
+// 使用 WSOL ATA 前后的指令: 创建账户并存入 wrap_lamports，交易结束时关闭账户换回 SOL
+pub fn wsol_account_instructions(
+    owner: &Pubkey,
+    wrap_lamports: u64,
+) -> Result<(Vec<Instruction>, Instruction)> {
+    let wsol_account = get_associated_token_address(owner, &WSOL);
+    let mut open = vec![create_ata_token_account_instr(spl_token::id(), &WSOL, owner)];
+    if wrap_lamports > 0 {
+        open.push(solana_sdk::system_instruction::transfer(
+            owner,
+            &wsol_account,
+            wrap_lamports,
+        ));
+        open.push(spl_token::instruction::sync_native(
+            &spl_token::id(),
+            &wsol_account,
+        )?);
+    }
+    let close = close_account(&spl_token::id(), &wsol_account, owner, owner, &[])?;
+    Ok((open, close))
+}
+
+// mint 对应的 Raydium 池子，查找一次后缓存
+static RAYDIUM_POOLS: Lazy<DashMap<Pubkey, Pubkey>> = Lazy::new(DashMap::new);
+
+// Raydium AMM v4 池子，卖出前按金库余额报价
+#[derive(Debug, Clone)]
+pub struct RaydiumPool {
+    pub address: Pubkey,
+    pub amm: AmmInfo,
+    // 卖出方向 (token -> WSOL) 的储备
+    pub token_reserves: u64,
+    pub sol_reserves: u64,
+}
+
+impl RaydiumPool {
+    pub fn sell_quote_with_slippage(&self, token_amount: u64, slippage_bps: u64) -> u64 {
+        let quoted = self
+            .amm
+            .swap_quote(token_amount, self.token_reserves, self.sol_reserves)
+            as u128;
+        (quoted * FEE_DENOMINATOR.saturating_sub(slippage_bps as u128) / FEE_DENOMINATOR) as u64
+    }
+}
+
+async fn raydium_pool_candidates(client: &RpcClient, token_mint: &Pubkey) -> Result<Vec<Pubkey>> {
+    if let Some(pool) = RAYDIUM_POOLS.get(token_mint) {
+        return Ok(vec![*pool]);
+    }
+
+    let mut candidates = Vec::new();
+    // token/WSOL 和 WSOL/token 两种方向的池子都可能存在
+    for (base, quote) in [(*token_mint, WSOL), (WSOL, *token_mint)] {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(AMM_INFO_LEN),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(BASE_MINT_OFFSET, base.as_ref())),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(QUOTE_MINT_OFFSET, quote.as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: None,
+                commitment: Some(CommitmentConfig::confirmed()),
+                min_context_slot: None,
+            },
+            with_context: None,
+            sort_results: None,
+        };
+        let accounts = client
+            .get_program_accounts_with_config(&RAYDIUM_PROGRAM_ID, config)
+            .await?;
+        candidates.extend(accounts.into_iter().map(|(address, _)| address));
+    }
+    Ok(candidates)
+}
+
+// 按 mint 查找 Raydium 池子，有多个时选 SOL 储备最多的
+pub async fn find_raydium_pool(token_mint: &Pubkey) -> Result<RaydiumPool> {
+    let app_state = APP_STATE
+        .get()
+        .ok_or_else(|| anyhow!("AppState not initialized"))?;
+    let client = &app_state.client;
+
+    let candidates = raydium_pool_candidates(client, token_mint).await?;
+    let amms = client
+        .get_multiple_accounts_with_commitment(&candidates, CommitmentConfig::processed())
+        .await?
+        .value
+        .into_iter()
+        .zip(candidates)
+        .filter_map(|(account, address)| Some((address, AmmInfo::decode(&account?.data).ok()?)))
+        .filter(|(_, amm)| amm.status == AMM_STATUS_SWAP)
+        .collect::<Vec<_>>();
+
+    let vaults = amms
+        .iter()
+        .flat_map(|(_, amm)| [amm.base_vault, amm.quote_vault])
+        .collect::<Vec<_>>();
+    let balances = client
+        .get_multiple_accounts_with_commitment(&vaults, CommitmentConfig::processed())
+        .await?
+        .value
+        .into_iter()
+        .map(|account| {
+            account
+                .and_then(|account| spl_token::state::Account::unpack(&account.data).ok())
+                .map(|token_account| token_account.amount)
+        })
+        .collect::<Vec<_>>();
+
+    let pool = amms
+        .into_iter()
+        .zip(balances.chunks(2))
+        .filter_map(|((address, amm), balances)| {
+            let (base, quote) = amm.reserves(balances[0]?, balances[1]?);
+            let (token_reserves, sol_reserves) = if amm.base_mint == *token_mint {
+                (base, quote)
+            } else {
+                (quote, base)
+            };
+            Some(RaydiumPool {
+                address,
+                amm,
+                token_reserves,
+                sol_reserves,
+            })
+        })
+        .max_by_key(|pool| pool.sol_reserves)
+        .ok_or_else(|| {
+            RAYDIUM_POOLS.remove(token_mint);
+            anyhow!("{} 没有可交易的 Raydium 池子", token_mint)
+        })?;
+
+    RAYDIUM_POOLS.insert(*token_mint, pool.address);
+    Ok(pool)
+}
+
+// 通过 Raydium AMM v4 卖出，使用不需要 OpenBook 市场账户的 swap_base_in_v2 账户列表
+pub async fn raydium_sell_with_tip(
+    wallet: &Wallet,
+    token_mint: Pubkey,
+    pool: &RaydiumPool,
+    token_amount: u64,
+    // 最少到手 SOL (lamports)，由调用方按池子储备报价
+    min_sol_output: u64,
+    tip: TipProfile,
+) -> Result<(), Error> {
+    let signer = wallet.keypair.as_ref();
+
+    println!("Raydium 出售代币: {}, 池子: {}", token_mint, pool.address);
+    println!("代币数量: {:.2}, 最少到手: {} lamports", token_amount, min_sol_output);
+
+    let instructions = raydium_sell_instructions(
+        &signer.pubkey(),
+        &token_mint,
+        pool,
+        token_amount,
+        min_sol_output,
+    )?;
 
     #[cfg(not(test))]
-    let sig = tx_pump_sell(&signer, instructions).await?;
+    tx_pump_amm_sell(signer, instructions, tip).await?;
 
     Ok(())
 }
 
+// Raydium 卖出指令：建 WSOL 账户、swap_base_in_v2、关闭 WSOL 账户
+pub fn raydium_sell_instructions(
+    seller: &Pubkey,
+    token_mint: &Pubkey,
+    pool: &RaydiumPool,
+    token_amount: u64,
+    min_sol_output: u64,
+) -> Result<Vec<Instruction>> {
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(RAYDIUM_SELL_SELECTOR);
+    data.extend_from_slice(&token_amount.to_le_bytes());
+    data.extend_from_slice(&min_sol_output.to_le_bytes());
+
+    let swap_instruction = Instruction::new_with_bytes(
+        PROXY_PROGRAM,
+        &data,
+        vec![
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(pool.address, false),
+            AccountMeta::new_readonly(AMM_AUTHORITY, false),
+            AccountMeta::new(pool.amm.base_vault, false),
+            AccountMeta::new(pool.amm.quote_vault, false),
+            AccountMeta::new(get_associated_token_address(seller, token_mint), false),
+            AccountMeta::new(get_associated_token_address(seller, &WSOL), false),
+            AccountMeta::new_readonly(*seller, true),
+            AccountMeta::new_readonly(RAYDIUM_PROGRAM_ID, false),
+        ],
+    );
+
+    let (open_wsol, close_wsol) = wsol_account_instructions(seller, 0)?;
+    let mut instructions = open_wsol;
+    instructions.push(swap_instruction);
+    instructions.push(close_wsol);
+    Ok(instructions)
+}
+
 pub async fn get_bonding_curve_account(
     mint: &Pubkey,
     program_id: &Pubkey,
@@ -249,3 +689,96 @@ pub fn get_pda(mint: &Pubkey, program_id: &Pubkey) -> Result<Pubkey> {
     let (bonding_curve, _bump) = Pubkey::find_program_address(&seeds, program_id);
     Ok(bonding_curve)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pump_amm_pool(mint: Pubkey) -> PumpAmmPool {
+        PumpAmmPool {
+            address: Pubkey::new_unique(),
+            pool: PoolAccount {
+                pool_bump: 255,
+                index: 0,
+                creator: Pubkey::new_unique(),
+                base_mint: mint,
+                quote_mint: spl_token::native_mint::id(),
+                lp_mint: Pubkey::new_unique(),
+                pool_base_token_account: Pubkey::new_unique(),
+                pool_quote_token_account: Pubkey::new_unique(),
+                lp_supply: 0,
+                coin_creator: Pubkey::new_unique(),
+            },
+            protocol_fee_recipient: Pubkey::new_unique(),
+            state: PoolState {
+                base_reserves: 200_000_000_000_000,
+                quote_reserves: 85_000_000_000,
+                lp_fee_basis_points: 20,
+                protocol_fee_basis_points: 5,
+                coin_creator_fee_basis_points: 5,
+            },
+        }
+    }
+
+    #[test]
+    fn test_pump_amm_sell_instructions() {
+        let seller = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let pool = pump_amm_pool(mint);
+
+        let instructions = pump_amm_sell_instructions(&seller, &pool, 1_000_000, 5_000).unwrap();
+        assert_eq!(instructions.len(), 3);
+
+        // 卖出夹在建 WSOL 账户和关闭 WSOL 账户之间
+        let sell = &instructions[1];
+        assert_eq!(sell.program_id, PROXY_PROGRAM);
+        assert_eq!(&sell.data[..8], PUMP_AMM_SELL_SELECTOR);
+        assert_eq!(sell.data[8..16], 1_000_000u64.to_le_bytes());
+        assert_eq!(sell.data[16..24], 5_000u64.to_le_bytes());
+        assert_eq!(sell.accounts.len(), 19);
+        assert_eq!(sell.accounts[0].pubkey, pool.address);
+        assert_eq!(sell.accounts[1].pubkey, seller);
+        assert!(sell.accounts[1].is_signer);
+        assert_eq!(sell.accounts[3].pubkey, mint);
+        assert_eq!(instructions[2].program_id, spl_token::id());
+    }
+
+    #[test]
+    fn test_raydium_sell_instructions() {
+        let seller = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let pool = RaydiumPool {
+            address: Pubkey::new_unique(),
+            amm: AmmInfo {
+                status: AMM_STATUS_SWAP,
+                swap_fee_numerator: 25,
+                swap_fee_denominator: 10_000,
+                base_need_take_pnl: 0,
+                quote_need_take_pnl: 0,
+                base_vault: Pubkey::new_unique(),
+                quote_vault: Pubkey::new_unique(),
+                base_mint: mint,
+                quote_mint: WSOL,
+            },
+            token_reserves: 200_000_000_000_000,
+            sol_reserves: 85_000_000_000,
+        };
+
+        let instructions =
+            raydium_sell_instructions(&seller, &mint, &pool, 1_000_000, 5_000).unwrap();
+        assert_eq!(instructions.len(), 3);
+
+        let swap = &instructions[1];
+        assert_eq!(swap.program_id, PROXY_PROGRAM);
+        assert_eq!(&swap.data[..8], RAYDIUM_SELL_SELECTOR);
+        assert_eq!(swap.data[8..16], 1_000_000u64.to_le_bytes());
+        assert_eq!(swap.data[16..24], 5_000u64.to_le_bytes());
+        assert_eq!(swap.accounts.len(), 9);
+        assert_eq!(swap.accounts[1].pubkey, pool.address);
+        assert_eq!(swap.accounts[5].pubkey, get_associated_token_address(&seller, &mint));
+        assert_eq!(swap.accounts[6].pubkey, get_associated_token_address(&seller, &WSOL));
+        assert_eq!(swap.accounts[7].pubkey, seller);
+        assert!(swap.accounts[7].is_signer);
+        assert_eq!(swap.accounts[8].pubkey, RAYDIUM_PROGRAM_ID);
+    }
+}
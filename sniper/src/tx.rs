@@ -29,9 +29,9 @@ use tracing::{error, info, warn};
 
 use crate::api::APP_STATE;
 use crate::api::AppState;
+use crate::services::wallet_pool::Wallet;
 use crate::config::{
-    JITO_FEE, JITO_RPC_ENDPOINTS, NONCE_PUBKEY, ZERO_SLOT_BUY_FEE, ZERO_SLOT_RPC_ENDPOINTS,
-    ZERO_SLOT_SELL_FEE,
+    EMERGENCY_CU_PRICE, EMERGENCY_SELL_FEE, JITO_FEE, JITO_RPC_ENDPOINTS, ZERO_SLOT_BUY_FEE, ZERO_SLOT_RPC_ENDPOINTS, ZERO_SLOT_SELL_FEE,
 };
 use solana_client::rpc_config::RpcSendTransactionConfig;
 use solana_sdk::commitment_config::CommitmentLevel;
@@ -39,11 +39,10 @@ use solana_sdk::pubkey;
 use solana_sdk::transaction::Transaction;
 
 lazy_static::lazy_static! {
-    pub static ref RECENT_BLOCKHASH: Arc<RwLock<Hash>> = Arc::new(RwLock::new(Hash::default()));
     pub static ref SELL_RECENT_BLOCKHASH: Arc<RwLock<Hash>> = Arc::new(RwLock::new(Hash::default()));
 }
 lazy_static::lazy_static! {
-    static ref JITO_TIP_ACCOUNTS: [Pubkey; 8] = [
+    pub static ref JITO_TIP_ACCOUNTS: [Pubkey; 8] = [
         Pubkey::from_str("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5").unwrap(),
         Pubkey::from_str("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe").unwrap(),
         Pubkey::from_str("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY").unwrap(),
@@ -54,7 +53,7 @@ lazy_static::lazy_static! {
         Pubkey::from_str("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT").unwrap(),
     ];
 
-    static ref ZEROSLOT_TIP_ACCOUNTS: [Pubkey; 5] = [
+    pub static ref ZEROSLOT_TIP_ACCOUNTS: [Pubkey; 5] = [
         Pubkey::from_str("Eb2KpSC8uMt9GmzyAEm5Eb1AAAgTjRaXWFjKyFXHZxF3").unwrap(),
         Pubkey::from_str("FCjUJZ1qozm1e8romw216qyfQMaaWKxWsuySnumVCCNe").unwrap(),
         Pubkey::from_str("ENxTEjSQ1YabmUpXAdCgevnHQ9MHdLv8tzFiuiYJqa13").unwrap(),
@@ -124,28 +123,28 @@ async fn first_get_nonce_state(rpc: &RpcClient, nonce_pubkey: &Pubkey) -> Result
     Ok(NonceState::Initialized(nonce_data))
 }
 
-pub async fn update_nonce(state: &AppState, nonce_pubkey: Arc<Pubkey>) -> Result<()> {
+pub async fn update_nonce(state: &AppState, wallet: &Wallet) -> Result<()> {
     let client = state.client.clone();
-    let nonce_state = get_nonce_state(&client, &nonce_pubkey).await?;
+    let nonce_state = get_nonce_state(&client, &wallet.nonce).await?;
     let new_blockhash = extract_blockhash(nonce_state)?;
 
-    let mut blockhash_lock = RECENT_BLOCKHASH.write().await;
+    let mut blockhash_lock = wallet.blockhash.write().await;
     *blockhash_lock = new_blockhash;
 
-    println!("手动更新 RECENT_BLOCKHASH 为: {}", new_blockhash);
+    println!("手动更新钱包 {} 的 blockhash 为: {}", wallet.name, new_blockhash);
     Ok(())
 }
 
 // 启动 blockhash 更新器
-pub async fn start_blockhash_fetcher(state: &AppState, nonce_pubkey: Arc<Pubkey>) -> Result<()> {
+pub async fn start_blockhash_fetcher(state: &AppState, wallet: &Wallet) -> Result<()> {
     let client = state.client.clone();
-    let nonce_state = first_get_nonce_state(&client, &nonce_pubkey).await?;
+    let nonce_state = first_get_nonce_state(&client, &wallet.nonce).await?;
     let blockhash = extract_blockhash(nonce_state)?;
 
-    let mut blockhash_lock = RECENT_BLOCKHASH.write().await;
+    let mut blockhash_lock = wallet.blockhash.write().await;
     *blockhash_lock = blockhash;
 
-    println!("初始化 RECENT_BLOCKHASH 地址: {}", blockhash);
+    println!("初始化钱包 {} 的 blockhash: {}", wallet.name, blockhash);
     Ok(())
 }
 
@@ -167,10 +166,11 @@ pub async fn keep_alive_loop() {
 }
 
 pub async fn tx_pump_buy(
-    keypair: &Keypair,
+    wallet: &Wallet,
     mut instructions: Vec<Instruction>,
 ) -> Result<Vec<String>> {
-    let nonce_pubkey = Pubkey::from_str(NONCE_PUBKEY.as_str()).unwrap();
+    let keypair = wallet.keypair.as_ref();
+    let nonce_pubkey = wallet.nonce;
 
     let unit_limit = 77000;
     // let unit_price = 193464;
@@ -180,7 +180,7 @@ pub async fn tx_pump_buy(
     // Set0.05 lamports per compute unit
     // 0.0007 * 0.05 = 0.000035 SOL;
 
-    let recent_blockhash = *RECENT_BLOCKHASH.read().await;
+    let recent_blockhash = *wallet.blockhash.read().await;
 
     let instr_advance_nonce_account =
         system_instruction::advance_nonce_account(&nonce_pubkey, &keypair.pubkey());
@@ -319,13 +319,55 @@ pub async fn tx_pump_buy(
     Ok(vec![])
 }
 
+// 卖出的小费配置，开发者砸盘等紧急退出时使用更高的小费
+#[derive(Debug, Clone, Copy)]
+pub struct TipProfile {
+    pub zero_slot_fee: f64,
+    // 优先费 (micro-lamports / CU)，0 表示不设置
+    pub compute_unit_price: u64,
+}
+
+impl TipProfile {
+    pub fn normal() -> Self {
+        Self {
+            zero_slot_fee: unsafe { *ZERO_SLOT_SELL_FEE },
+            compute_unit_price: 0,
+        }
+    }
+
+    pub fn emergency() -> Self {
+        Self {
+            zero_slot_fee: *EMERGENCY_SELL_FEE,
+            compute_unit_price: *EMERGENCY_CU_PRICE,
+        }
+    }
+}
+
 pub async fn tx_pump_sell(
+    keypair: &Keypair,
+    instructions: Vec<Instruction>,
+    tip: TipProfile,
+) -> Result<Vec<String>> {
+    tx_sell(keypair, instructions, tip, 75000).await
+}
+
+// PumpSwap 卖出要创建和关闭 WSOL 账户，计算单元更多
+pub async fn tx_pump_amm_sell(
+    keypair: &Keypair,
+    instructions: Vec<Instruction>,
+    tip: TipProfile,
+) -> Result<Vec<String>> {
+    tx_sell(keypair, instructions, tip, 160_000).await
+}
+
+async fn tx_sell(
     keypair: &Keypair,
     mut instructions: Vec<Instruction>,
+    tip: TipProfile,
+    unit_limit: u32,
 ) -> Result<Vec<String>> {
     let app_state = APP_STATE.get().expect("AppState not initialized");
     let client = &app_state.client;
-    let unit_limit = 75000;
     // let unit_price = 50000;
 
     let recent_blockhash = client
@@ -339,13 +381,16 @@ pub async fn tx_pump_sell(
     //     solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(unit_price);
     instructions.insert(1, modify_compute_units);
     // instructions.insert(2, add_priority_fee);
+    if tip.compute_unit_price > 0 {
+        let add_priority_fee =
+            solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(
+                tip.compute_unit_price,
+            );
+        instructions.insert(2, add_priority_fee);
+    }
 
     // ---------------------- 0Slot HTTP -------------------------
-    let mut slot_tip = 0.0;
-    unsafe {
-        slot_tip = *ZERO_SLOT_SELL_FEE; // 0.001 SOLÏ
-    }
-    let slot_tip_lamports = (slot_tip * 1_000_000_000.0) as u64;
+    let slot_tip_lamports = (tip.zero_slot_fee * 1_000_000_000.0) as u64;
     let tip_account = get_0slot_tip_account();
     let tip_instruction =
         system_instruction::transfer(&keypair.pubkey(), &tip_account, slot_tip_lamports);